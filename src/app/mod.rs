@@ -1,5 +1,7 @@
 pub mod plugin;
+pub mod recording;
 pub mod state;
 
 pub use plugin::AppPlugin;
-pub use state::{reset_simulation, SimulationState};
+pub use recording::{BodyFrame, BodyQuery, Frame, Playback, Recording, SimulationControl};
+pub use state::{reset_simulation, SimulationSnapshot, SimulationState};
@@ -1,7 +1,12 @@
+use avian2d::prelude::PhysicsSystems;
 use bevy::prelude::*;
 use bevy::window::WindowResolution;
 
-use super::state::{reset_simulation, SimulationState};
+use super::recording::{
+    advance_playback, apply_playback_frame, record_frame, resume_from_cursor, switch_branch,
+    Playback, Recording,
+};
+use super::state::{reset_simulation, take_snapshot, SimulationSnapshot, SimulationState};
 
 pub struct AppPlugin;
 
@@ -18,6 +23,27 @@ impl Plugin for AppPlugin {
             ..default()
         }))
         .init_resource::<SimulationState>()
-        .add_systems(PreUpdate, reset_simulation);
+        .init_resource::<SimulationSnapshot>()
+        .init_resource::<Recording>()
+        .init_resource::<Playback>()
+        .add_systems(PreUpdate, reset_simulation)
+        .add_systems(
+            Update,
+            (
+                resume_from_cursor,
+                switch_branch,
+                advance_playback,
+                apply_playback_frame,
+            )
+                .chain(),
+        )
+        .add_systems(
+            FixedPostUpdate,
+            (
+                // Right before the first step, however Play was triggered.
+                take_snapshot.before(PhysicsSystems::Prepare),
+                record_frame.after(PhysicsSystems::Writeback),
+            ),
+        );
     }
 }
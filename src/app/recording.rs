@@ -0,0 +1,410 @@
+use std::collections::VecDeque;
+
+use avian2d::prelude::*;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::state::SimulationState;
+use crate::editor::components::EditorEntity;
+use crate::project::{SceneEntityData, SceneEntityQuery, insert_scene_entity};
+use crate::simulation::{Charge, LocalField, SimulationClock};
+
+#[derive(Debug, Clone, Copy)]
+pub struct BodyFrame {
+    pub position: Vec2,
+    /// Radians.
+    pub rotation: f32,
+    pub linear_velocity: Vec2,
+    pub angular_velocity: f32,
+    /// Change in linear velocity over the last step, zero on the first one.
+    pub acceleration: Vec2,
+    pub kinetic_energy: f32,
+    /// Potential energy in the uniform gravity, zero at the origin.
+    pub potential_energy: f32,
+    /// Lorentz force from the fields the body was in during the last step.
+    pub field_force: Vec2,
+}
+
+/// Motion of a body after a physics step, as recorded into a `Frame`.
+#[derive(QueryData)]
+pub struct BodyQuery {
+    pub entity: Entity,
+    pub position: &'static Position,
+    pub rotation: &'static Rotation,
+    pub rigid_body: Option<&'static RigidBody>,
+    pub linear_velocity: Option<&'static LinearVelocity>,
+    pub angular_velocity: Option<&'static AngularVelocity>,
+    pub mass: Option<&'static ComputedMass>,
+    pub inertia: Option<&'static ComputedAngularInertia>,
+    pub charge: Option<&'static Charge>,
+    pub local_field: Option<&'static LocalField>,
+}
+
+impl BodyQueryItem<'_, '_> {
+    /// `previous` is the same body one step earlier and the time between them.
+    pub fn to_frame(&self, previous: Option<(&BodyFrame, f32)>, gravity: Vec2) -> BodyFrame {
+        let linear_velocity = self.linear_velocity.map(|v| v.0).unwrap_or_default();
+        let angular_velocity = self.angular_velocity.map(|v| v.0).unwrap_or_default();
+
+        let acceleration = match previous {
+            Some((previous, dt)) if dt > 0.0 => (linear_velocity - previous.linear_velocity) / dt,
+            _ => Vec2::ZERO,
+        };
+
+        // Static and kinematic bodies have infinite mass.
+        let (kinetic_energy, potential_energy) =
+            if self.rigid_body.is_some_and(|body| body.is_dynamic()) {
+                let mass = self.mass.map_or(0.0, |mass| mass.value());
+                let inertia = self.inertia.map_or(0.0, |inertia| inertia.value());
+                (
+                    0.5 * mass * linear_velocity.length_squared()
+                        + 0.5 * inertia * angular_velocity * angular_velocity,
+                    -mass * gravity.dot(self.position.0),
+                )
+            } else {
+                (0.0, 0.0)
+            };
+
+        let field_force = match (self.charge, self.local_field) {
+            (Some(charge), Some(local)) => local.lorentz_force(charge.value, linear_velocity),
+            _ => Vec2::ZERO,
+        };
+
+        BodyFrame {
+            position: self.position.0,
+            rotation: self.rotation.as_radians(),
+            linear_velocity,
+            angular_velocity,
+            acceleration,
+            kinetic_energy,
+            potential_energy,
+            field_force,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Frame {
+    /// Simulation time after the step, in seconds.
+    pub time: f32,
+    pub bodies: EntityHashMap<BodyFrame>,
+}
+
+/// One run of the simulation. Resuming from a past frame starts a new branch
+/// that shares the frames up to that point.
+pub struct Branch {
+    pub name: String,
+    /// Branch this one was resumed from, and the time it was resumed at.
+    pub parent: Option<(usize, f32)>,
+    /// Entity properties when the branch started; frames only store motion.
+    pub properties: Vec<(Entity, SceneEntityData)>,
+    pub frames: VecDeque<Frame>,
+    /// Whether frames from the start of the run were dropped for `capacity`.
+    pub wrapped: bool,
+}
+
+impl Branch {
+    fn root(properties: Vec<(Entity, SceneEntityData)>) -> Self {
+        Self {
+            name: "Run 1".to_string(),
+            parent: None,
+            properties,
+            frames: VecDeque::new(),
+            wrapped: false,
+        }
+    }
+}
+
+/// Ring buffers of the most recent physics steps, one per branch.
+#[derive(Resource)]
+pub struct Recording {
+    /// Never empty.
+    pub branches: Vec<Branch>,
+    /// Branch being shown and recorded into.
+    pub active: usize,
+    /// Oldest frames of a branch are dropped past this many.
+    pub capacity: usize,
+}
+
+impl Default for Recording {
+    fn default() -> Self {
+        Self {
+            branches: vec![Branch::root(Vec::new())],
+            active: 0,
+            // One minute at the default 64 Hz timestep.
+            capacity: 64 * 60,
+        }
+    }
+}
+
+impl Recording {
+    pub fn active_branch(&self) -> &Branch {
+        &self.branches[self.active]
+    }
+
+    pub fn frames(&self) -> &VecDeque<Frame> {
+        &self.active_branch().frames
+    }
+
+    pub fn push(&mut self, frame: Frame) {
+        self.branches[self.active].frames.push_back(frame);
+        self.truncate_front();
+    }
+
+    /// Drops the oldest frames of every branch until each fits `capacity`.
+    pub fn truncate_front(&mut self) {
+        let capacity = self.capacity.max(1);
+        for branch in &mut self.branches {
+            while branch.frames.len() > capacity {
+                branch.frames.pop_front();
+                branch.wrapped = true;
+            }
+        }
+    }
+
+    /// Starts over with a single empty branch.
+    pub fn clear(&mut self, properties: Vec<(Entity, SceneEntityData)>) {
+        self.branches = vec![Branch::root(properties)];
+        self.active = 0;
+    }
+
+    /// Starts a new branch from frame `index` of the active one and makes it active.
+    pub fn fork(&mut self, index: usize, properties: Vec<(Entity, SceneEntityData)>) {
+        let parent = self.active_branch();
+        let Some(fork_frame) = parent.frames.get(index) else {
+            return;
+        };
+        let branch = Branch {
+            name: format!("Run {}", self.branches.len() + 1),
+            parent: Some((self.active, fork_frame.time)),
+            properties,
+            frames: parent.frames.iter().take(index + 1).cloned().collect(),
+            wrapped: parent.wrapped,
+        };
+        self.branches.push(branch);
+        self.active = self.branches.len() - 1;
+    }
+
+    /// Names of the entities in the active branch, as they were when it
+    /// started, so that entities deleted since still have one.
+    pub fn entity_names(&self) -> EntityHashMap<String> {
+        self.active_branch()
+            .properties
+            .iter()
+            .map(|(entity, data)| (*entity, data.name.clone()))
+            .collect()
+    }
+
+    /// Last frame of the active branch recorded at or before `time`.
+    pub fn index_at(&self, time: f32) -> usize {
+        self.frames()
+            .partition_point(|frame| frame.time <= time)
+            .saturating_sub(1)
+    }
+}
+
+/// Position of the timeline cursor.
+#[derive(Resource)]
+pub struct Playback {
+    /// Recorded frame being shown, `None` while the live simulation is shown.
+    pub cursor: Option<usize>,
+    pub playing: bool,
+    /// Playback rate relative to the recorded time, negative plays backwards.
+    pub speed: f32,
+    pub time: f32,
+    /// Branch to show next, set by the timeline.
+    pub switch_to: Option<usize>,
+}
+
+impl Default for Playback {
+    fn default() -> Self {
+        Self {
+            cursor: None,
+            playing: false,
+            speed: 1.0,
+            time: 0.0,
+            switch_to: None,
+        }
+    }
+}
+
+impl Playback {
+    pub fn seek(&mut self, recording: &Recording, index: usize) {
+        let Some(frame) = recording.frames().get(index) else {
+            return;
+        };
+        self.cursor = Some(index);
+        self.time = frame.time;
+    }
+
+    pub fn go_live(&mut self) {
+        self.cursor = None;
+        self.playing = false;
+    }
+}
+
+/// Run state together with its recording, for the status bar and timeline.
+#[derive(SystemParam)]
+pub struct SimulationControl<'w> {
+    pub state: ResMut<'w, SimulationState>,
+    pub recording: ResMut<'w, Recording>,
+    pub playback: ResMut<'w, Playback>,
+}
+
+pub fn record_frame(
+    state: Res<SimulationState>,
+    playback: Res<Playback>,
+    mut recording: ResMut<Recording>,
+    clock: Res<SimulationClock>,
+    gravity: Res<Gravity>,
+    bodies: Query<BodyQuery, With<EditorEntity>>,
+) {
+    if !state.is_running || playback.cursor.is_some() {
+        return;
+    }
+
+    let previous = recording.frames().back();
+    let bodies = bodies
+        .iter()
+        .map(|item| {
+            let previous = previous.and_then(|frame| {
+                let body = frame.bodies.get(&item.entity)?;
+                Some((body, clock.0 - frame.time))
+            });
+            (item.entity, item.to_frame(previous, gravity.0))
+        })
+        .collect();
+
+    recording.push(Frame {
+        time: clock.0,
+        bodies,
+    });
+}
+
+/// Moves the cursor while the timeline is playing back.
+pub fn advance_playback(
+    time: Res<Time>,
+    recording: Res<Recording>,
+    mut playback: ResMut<Playback>,
+) {
+    if !playback.playing || playback.cursor.is_none() {
+        return;
+    }
+    let (Some(first), Some(last)) = (recording.frames().front(), recording.frames().back()) else {
+        playback.go_live();
+        return;
+    };
+
+    let target = playback.time + time.delta_secs() * playback.speed;
+    if target <= first.time || target >= last.time {
+        playback.playing = false;
+    }
+    let target = target.clamp(first.time, last.time);
+    let index = recording.index_at(target);
+    playback.cursor = Some(index);
+    playback.time = target;
+}
+
+/// Starting the simulation while a past frame is shown resumes from that frame
+/// in a new branch, so the original run stays available for comparison.
+pub fn resume_from_cursor(
+    state: Res<SimulationState>,
+    mut playback: ResMut<Playback>,
+    mut recording: ResMut<Recording>,
+    query: Query<SceneEntityQuery, With<EditorEntity>>,
+) {
+    if !state.is_running {
+        return;
+    }
+    let Some(index) = playback.cursor else {
+        return;
+    };
+    playback.go_live();
+
+    if index + 1 < recording.frames().len() {
+        let properties = query
+            .iter()
+            .map(|item| (item.entity, item.to_data()))
+            .collect();
+        recording.fork(index, properties);
+    }
+}
+
+/// Shows another branch at its latest frame, with the properties it ran with.
+pub fn switch_branch(
+    mut commands: Commands,
+    mut state: ResMut<SimulationState>,
+    mut recording: ResMut<Recording>,
+    mut playback: ResMut<Playback>,
+) {
+    let Some(branch) = playback.switch_to.take() else {
+        return;
+    };
+    if branch >= recording.branches.len() {
+        return;
+    }
+    state.is_running = false;
+    recording.active = branch;
+
+    // Motion is overwritten with the recorded frame by `apply_playback_frame`.
+    for (entity, data) in &recording.active_branch().properties {
+        if let Ok(mut entity_commands) = commands.get_entity(*entity) {
+            insert_scene_entity(&mut entity_commands, data);
+        }
+    }
+
+    let last = recording.frames().len().saturating_sub(1);
+    playback.seek(&recording, last);
+}
+
+/// Shows the frame under the cursor.
+pub fn apply_playback_frame(
+    recording: Res<Recording>,
+    playback: Res<Playback>,
+    mut clock: ResMut<SimulationClock>,
+    mut shown: Local<Option<(usize, usize)>>,
+    mut bodies: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Position,
+            &mut Rotation,
+            Option<&mut LinearVelocity>,
+            Option<&mut AngularVelocity>,
+        ),
+        With<EditorEntity>,
+    >,
+) {
+    let Some(index) = playback.cursor else {
+        *shown = None;
+        return;
+    };
+    if *shown == Some((recording.active, index)) {
+        return;
+    }
+    *shown = Some((recording.active, index));
+    let Some(frame) = recording.frames().get(index) else {
+        return;
+    };
+    clock.0 = frame.time;
+
+    for (entity, mut transform, mut position, mut rotation, linear_velocity, angular_velocity) in
+        &mut bodies
+    {
+        let Some(body) = frame.bodies.get(&entity) else {
+            continue;
+        };
+        transform.translation = body.position.extend(transform.translation.z);
+        transform.rotation = Quat::from_rotation_z(body.rotation);
+        position.0 = body.position;
+        *rotation = Rotation::radians(body.rotation);
+        if let Some(mut velocity) = linear_velocity {
+            velocity.0 = body.linear_velocity;
+        }
+        if let Some(mut velocity) = angular_velocity {
+            velocity.0 = body.angular_velocity;
+        }
+    }
+}
@@ -1,8 +1,12 @@
-use avian2d::prelude::LinearVelocity;
+use avian2d::prelude::Sleeping;
+use bevy::ecs::entity::EntityHashSet;
 use bevy::prelude::*;
 
-use crate::shared::InitialState;
+use super::recording::{Playback, Recording};
 use crate::editor::components::EditorEntity;
+use crate::editor::{EditHistory, PlacedEntities, SelectionState};
+use crate::project::{SceneEntityData, SceneEntityQuery, insert_scene_entity};
+use crate::simulation::SimulationClock;
 
 #[derive(Resource)]
 pub struct SimulationState {
@@ -21,15 +25,111 @@ impl Default for SimulationState {
     }
 }
 
+pub struct EntitySnapshot {
+    pub entity: Entity,
+    pub data: SceneEntityData,
+    pub sleeping: bool,
+}
+
+/// Every editor entity as it was right before the first physics step after Play.
+/// `None` while the scene is in its edited state.
+#[derive(Resource, Default)]
+pub struct SimulationSnapshot {
+    pub entities: Option<Vec<EntitySnapshot>>,
+}
+
+pub fn take_snapshot(
+    state: Res<SimulationState>,
+    mut snapshot: ResMut<SimulationSnapshot>,
+    mut recording: ResMut<Recording>,
+    query: Query<(SceneEntityQuery, Has<Sleeping>), With<EditorEntity>>,
+) {
+    if !state.is_running || snapshot.entities.is_some() {
+        return;
+    }
+
+    let mut entities: Vec<_> = query
+        .iter()
+        .map(|(item, sleeping)| EntitySnapshot {
+            entity: item.entity,
+            data: item.to_data(),
+            sleeping,
+        })
+        .collect();
+    entities.sort_by_key(|snapshot| snapshot.entity);
+    recording.clear(
+        entities
+            .iter()
+            .map(|snapshot| (snapshot.entity, snapshot.data.clone()))
+            .collect(),
+    );
+    snapshot.entities = Some(entities);
+}
+
+/// Puts every editor entity back to the snapshot. Bodies removed during the
+/// run are respawned and bodies added during the run are despawned.
 pub fn reset_simulation(
-    mut query: Query<(&InitialState, &mut Transform, &mut LinearVelocity), With<EditorEntity>>,
+    mut commands: Commands,
     mut state: ResMut<SimulationState>,
+    mut snapshot: ResMut<SimulationSnapshot>,
+    mut placed_entities: ResMut<PlacedEntities>,
+    mut selection: ResMut<SelectionState>,
+    mut recording: ResMut<Recording>,
+    mut playback: ResMut<Playback>,
+    mut clock: ResMut<SimulationClock>,
+    mut history: ResMut<EditHistory>,
+    existing: Query<Entity, With<EditorEntity>>,
 ) {
-    if state.needs_reset {
-        state.needs_reset = false;
-        for (initial_state, mut transform, mut velocity) in query.iter_mut() {
-            *transform = initial_state.transform;
-            *velocity = LinearVelocity(initial_state.velocity);
+    if !state.needs_reset {
+        return;
+    }
+    state.needs_reset = false;
+    playback.go_live();
+    clock.0 = 0.0;
+
+    let Some(entities) = snapshot.entities.take() else {
+        recording.clear(Vec::new());
+        return;
+    };
+
+    let snapshot_entities: EntityHashSet = entities.iter().map(|s| s.entity).collect();
+    for entity in &existing {
+        if !snapshot_entities.contains(&entity) {
+            commands.entity(entity).despawn();
+        }
+    }
+
+    let mut restored = Vec::with_capacity(entities.len());
+    let mut properties = Vec::with_capacity(entities.len());
+    for entity_snapshot in &entities {
+        let mut entity_commands = if existing.contains(entity_snapshot.entity) {
+            commands.entity(entity_snapshot.entity)
+        } else {
+            let respawned = commands.spawn(EditorEntity);
+            history.remap(entity_snapshot.entity, respawned.id());
+            respawned
+        };
+        insert_scene_entity(&mut entity_commands, &entity_snapshot.data);
+        if entity_snapshot.sleeping {
+            entity_commands.insert(Sleeping);
+        } else {
+            entity_commands.remove::<Sleeping>();
         }
+        restored.push(entity_commands.id());
+        properties.push((entity_commands.id(), entity_snapshot.data.clone()));
+    }
+    // Respawned entities have new ids, which the recording must know them by.
+    recording.clear(properties);
+
+    let restored_set: EntityHashSet = restored.iter().copied().collect();
+    let stale: Vec<_> = selection
+        .entities
+        .iter()
+        .copied()
+        .filter(|entity| !restored_set.contains(entity))
+        .collect();
+    for entity in stale {
+        selection.remove(entity);
     }
+    placed_entities.entities = restored;
 }
@@ -0,0 +1,105 @@
+use std::str::FromStr;
+
+use super::resources::BodySample;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quantity {
+    X,
+    Y,
+    VelocityX,
+    VelocityY,
+    Speed,
+    Angle,
+    AngularVelocity,
+}
+
+impl Quantity {
+    pub fn value(&self, sample: &BodySample) -> f32 {
+        match self {
+            Quantity::X => sample.body.position.x,
+            Quantity::Y => sample.body.position.y,
+            Quantity::VelocityX => sample.body.linear_velocity.x,
+            Quantity::VelocityY => sample.body.linear_velocity.y,
+            Quantity::Speed => sample.body.linear_velocity.length(),
+            Quantity::Angle => sample.body.rotation,
+            Quantity::AngularVelocity => sample.body.angular_velocity,
+        }
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Quantity::X),
+            "y" => Ok(Quantity::Y),
+            "vx" => Ok(Quantity::VelocityX),
+            "vy" => Ok(Quantity::VelocityY),
+            "speed" => Ok(Quantity::Speed),
+            "angle" => Ok(Quantity::Angle),
+            "omega" => Ok(Quantity::AngularVelocity),
+            _ => Err(format!(
+                "Unknown quantity \"{}\", expected one of x, y, vx, vy, speed, angle, omega",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    Greater,
+}
+
+/// Ends a batch run once a named entity crosses a threshold, written as
+/// `<entity>.<quantity><op><value>`, e.g. `Ball.y<0` or `Probe.speed>250`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StopCondition {
+    pub entity: String,
+    pub quantity: Quantity,
+    pub comparison: Comparison,
+    pub value: f32,
+}
+
+impl StopCondition {
+    pub fn holds(&self, sample: &BodySample) -> bool {
+        let value = self.quantity.value(sample);
+        match self.comparison {
+            Comparison::Less => value < self.value,
+            Comparison::Greater => value > self.value,
+        }
+    }
+}
+
+impl FromStr for StopCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (left, comparison, right) = if let Some((left, right)) = s.split_once('<') {
+            (left, Comparison::Less, right)
+        } else if let Some((left, right)) = s.split_once('>') {
+            (left, Comparison::Greater, right)
+        } else {
+            return Err(format!("Condition \"{}\" has no < or >", s));
+        };
+
+        // Entity names may contain dots, the quantity never does.
+        let (entity, quantity) = left
+            .trim()
+            .rsplit_once('.')
+            .ok_or_else(|| format!("Condition \"{}\" should start with <entity>.<quantity>", s))?;
+        let value = right
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid threshold \"{}\"", right.trim()))?;
+
+        Ok(Self {
+            entity: entity.to_string(),
+            quantity: quantity.parse()?,
+            comparison,
+            value,
+        })
+    }
+}
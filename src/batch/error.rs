@@ -0,0 +1,48 @@
+use std::fmt;
+
+use crate::project::ProjectError;
+
+/// Why a batch run could not be set up or its results written.
+#[derive(Debug)]
+pub enum BatchError {
+    Project(ProjectError),
+    Io(std::io::Error),
+    InvalidArgument(String),
+    /// The stop condition names an entity the scene does not have.
+    UnknownEntity(String),
+}
+
+impl fmt::Display for BatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BatchError::Project(e) => write!(f, "{}", e),
+            BatchError::Io(e) => write!(f, "Failed to write results: {}", e),
+            BatchError::InvalidArgument(message) => write!(f, "{}", message),
+            BatchError::UnknownEntity(name) => {
+                write!(f, "Scene has no entity named \"{}\"", name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BatchError::Project(e) => Some(e),
+            BatchError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<ProjectError> for BatchError {
+    fn from(e: ProjectError) -> Self {
+        BatchError::Project(e)
+    }
+}
+
+impl From<std::io::Error> for BatchError {
+    fn from(e: std::io::Error) -> Self {
+        BatchError::Io(e)
+    }
+}
@@ -0,0 +1,15 @@
+pub mod condition;
+pub mod error;
+pub mod output;
+pub mod plugin;
+pub mod resources;
+pub mod runner;
+pub mod sweep;
+
+pub use condition::StopCondition;
+pub use error::BatchError;
+pub use output::write_sweep_csv;
+pub use plugin::BatchPlugin;
+pub use resources::{BatchConfig, BatchRun, BodySample, StopReason};
+pub use runner::run_batch;
+pub use sweep::{Measure, Sweep, SweepParameter, SweepRow, run_sweep};
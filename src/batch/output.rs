@@ -0,0 +1,49 @@
+use std::io::Write;
+
+use super::resources::StopReason;
+use super::sweep::{Sweep, SweepRow};
+use crate::project::trajectory::csv_field;
+
+/// One row per combination: the parameter values, how the run ended and the
+/// measured quantities. Measures of entities that no longer exist are left empty.
+pub fn write_sweep_csv(
+    sweep: &Sweep,
+    rows: &[SweepRow],
+    writer: &mut impl Write,
+) -> std::io::Result<()> {
+    let header: Vec<_> = sweep
+        .parameters
+        .iter()
+        .map(|parameter| parameter.label())
+        .chain(["steps", "time", "stopped"].map(str::to_string))
+        .chain(
+            sweep
+                .measures
+                .iter()
+                .map(|measure| measure.label().to_string()),
+        )
+        .map(|label| csv_field(&label))
+        .collect();
+    writeln!(writer, "{}", header.join(","))?;
+
+    for row in rows {
+        let stopped = match row.stopped {
+            Some(StopReason::Condition) => "condition",
+            _ => "steps",
+        };
+        let fields: Vec<_> = row
+            .values
+            .iter()
+            .map(f32::to_string)
+            .chain([row.steps.to_string(), row.time.to_string()])
+            .chain([stopped.to_string()])
+            .chain(
+                row.measured
+                    .iter()
+                    .map(|value| value.map(|v| v.to_string()).unwrap_or_default()),
+            )
+            .collect();
+        writeln!(writer, "{}", fields.join(","))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use avian2d::prelude::PhysicsSystems;
+use bevy::mesh::MeshPlugin;
+use bevy::prelude::*;
+
+use super::resources::BatchRun;
+use super::runner::sample_step;
+use crate::app::SimulationState;
+use crate::simulation::SimulationPlugin;
+
+/// Runs `SimulationPlugin` without a window, renderer or UI. Time only moves
+/// when the app is updated, by exactly one physics step per update.
+pub struct BatchPlugin;
+
+impl Plugin for BatchPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((
+            MinimalPlugins,
+            TransformPlugin,
+            AssetPlugin::default(),
+            // Colliders can be built from meshes, so avian expects mesh assets.
+            MeshPlugin,
+        ))
+        .add_plugins(SimulationPlugin)
+        .insert_resource(SimulationState {
+            is_running: true,
+            ..default()
+        })
+        .init_resource::<BatchRun>()
+        .add_systems(
+            FixedPostUpdate,
+            sample_step.after(PhysicsSystems::Writeback),
+        );
+    }
+}
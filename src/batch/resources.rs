@@ -0,0 +1,77 @@
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+
+use super::condition::StopCondition;
+use crate::app::BodyFrame;
+use crate::project::{TrajectoryData, TrajectorySample};
+use crate::simulation::ConservationDiagnostics;
+
+/// How long a batch run lasts and what it records.
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// Upper bound on physics steps, whether or not `until` ever holds.
+    pub max_steps: u64,
+    pub until: Option<StopCondition>,
+    /// Record every n-th step; 0 records only the final state.
+    pub sample_every: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            // Ten seconds at the default 64 Hz timestep.
+            max_steps: 640,
+            until: None,
+            sample_every: 1,
+        }
+    }
+}
+
+/// State of one body after a physics step.
+#[derive(Debug, Clone)]
+pub struct BodySample {
+    pub step: u64,
+    /// Simulation time after the step, in seconds.
+    pub time: f32,
+    pub entity: Entity,
+    pub name: String,
+    pub body: BodyFrame,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    StepLimit,
+    Condition,
+}
+
+/// Progress and results of the batch run in progress.
+#[derive(Resource, Default)]
+pub struct BatchRun {
+    pub config: BatchConfig,
+    pub steps: u64,
+    /// Simulation time after the last step, in seconds.
+    pub time: f32,
+    pub samples: Vec<BodySample>,
+    /// Set once the run should end.
+    pub stopped: Option<StopReason>,
+    /// Drift at the end of the run, when the scene measures it.
+    pub conservation: Option<ConservationDiagnostics>,
+}
+
+impl BatchRun {
+    /// Recorded samples per entity; `interval` is the time between them.
+    pub fn trajectories(&self, interval: f32) -> TrajectoryData {
+        let mut series: EntityHashMap<Vec<TrajectorySample>> = EntityHashMap::default();
+        let mut names = EntityHashMap::default();
+        for sample in &self.samples {
+            names
+                .entry(sample.entity)
+                .or_insert_with(|| sample.name.clone());
+            series
+                .entry(sample.entity)
+                .or_default()
+                .push(TrajectorySample::from_frame(sample.time, &sample.body));
+        }
+        TrajectoryData::from_series(series, &names, interval)
+    }
+}
@@ -0,0 +1,129 @@
+use std::time::Duration;
+
+use avian2d::prelude::*;
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+
+use super::error::BatchError;
+use super::plugin::BatchPlugin;
+use super::resources::{BatchConfig, BatchRun, BodySample, StopReason};
+use crate::app::{BodyFrame, BodyQuery};
+use crate::editor::{EditorEntity, EntityName};
+use crate::project::{SceneData, spawn_scene_entity};
+use crate::simulation::{ConservationDiagnostics, SimulationClock};
+
+/// Simulates `scene` headless until the step limit or the stop condition is
+/// reached, and returns what was recorded.
+pub fn run_batch(scene: &SceneData, config: BatchConfig) -> Result<BatchRun, BatchError> {
+    if config.max_steps == 0 {
+        return Err(BatchError::InvalidArgument(
+            "The step limit must be at least 1".to_string(),
+        ));
+    }
+    if let Some(condition) = &config.until
+        && !scene.entities.iter().any(|e| e.name == condition.entity)
+    {
+        return Err(BatchError::UnknownEntity(condition.entity.clone()));
+    }
+    let environment = &scene.environment;
+    let timestep_hz = environment.physics.timestep_hz;
+    if !(timestep_hz.is_finite() && timestep_hz > 0.0) {
+        return Err(BatchError::InvalidArgument(format!(
+            "Invalid timestep of {} Hz",
+            timestep_hz
+        )));
+    }
+    let step = Duration::from_secs_f64(1.0 / timestep_hz);
+
+    let mut app = App::new();
+    app.add_plugins(BatchPlugin)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(step))
+        .insert_resource(environment.gravity)
+        .insert_resource(environment.coulomb)
+        .insert_resource(environment.solver)
+        .insert_resource(environment.integrator)
+        .insert_resource(environment.physics)
+        .insert_resource(environment.conservation)
+        .insert_resource(BatchRun {
+            config,
+            ..default()
+        });
+
+    let world = app.world_mut();
+    world.resource_mut::<Time<Fixed>>().set_timestep(step);
+    let mut commands = world.commands();
+    for data in &scene.entities {
+        spawn_scene_entity(&mut commands, data);
+    }
+    world.flush();
+
+    app.finish();
+    app.cleanup();
+    while app.world().resource::<BatchRun>().stopped.is_none() {
+        app.update();
+    }
+
+    let world = app.world_mut();
+    let mut run = world.remove_resource::<BatchRun>().unwrap_or_default();
+    if environment.conservation.enabled {
+        run.conservation = world.remove_resource::<ConservationDiagnostics>();
+    }
+    Ok(run)
+}
+
+/// Counts the step that just ran, records it and decides whether to stop.
+pub fn sample_step(
+    mut run: ResMut<BatchRun>,
+    time: Res<Time<Physics>>,
+    clock: Res<SimulationClock>,
+    gravity: Res<Gravity>,
+    mut previous: Local<(f32, EntityHashMap<BodyFrame>)>,
+    bodies: Query<(BodyQuery, Option<&EntityName>), With<EditorEntity>>,
+) {
+    if run.stopped.is_some() || time.is_paused() {
+        return;
+    }
+    run.steps += 1;
+    run.time = clock.0;
+    let step = run.steps;
+
+    let (previous_time, previous_bodies) = &mut *previous;
+    let dt = clock.0 - *previous_time;
+    let mut bodies: Vec<_> = bodies.iter().collect();
+    bodies.sort_by_key(|(item, _)| item.entity);
+    let samples: Vec<_> = bodies
+        .into_iter()
+        .map(|(item, name)| BodySample {
+            step,
+            time: clock.0,
+            entity: item.entity,
+            name: name.map(|n| n.0.clone()).unwrap_or_default(),
+            body: item.to_frame(
+                previous_bodies.get(&item.entity).map(|body| (body, dt)),
+                gravity.0,
+            ),
+        })
+        .collect();
+    *previous_time = clock.0;
+    *previous_bodies = samples
+        .iter()
+        .map(|sample| (sample.entity, sample.body))
+        .collect();
+
+    let condition_holds = run.config.until.as_ref().is_some_and(|condition| {
+        samples
+            .iter()
+            .any(|sample| sample.name == condition.entity && condition.holds(sample))
+    });
+    if condition_holds {
+        run.stopped = Some(StopReason::Condition);
+    } else if step >= run.config.max_steps {
+        run.stopped = Some(StopReason::StepLimit);
+    }
+
+    let every = run.config.sample_every;
+    if run.stopped.is_some() || (every > 0 && step % every == 0) {
+        run.samples.extend(samples);
+    }
+}
@@ -0,0 +1,321 @@
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::condition::Quantity;
+use super::error::BatchError;
+use super::resources::{BatchConfig, StopReason};
+use super::runner::run_batch;
+use crate::project::{SceneData, SceneEntityData};
+
+/// Scene property of a named entity that a sweep can vary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Property {
+    X,
+    Y,
+    VelocityX,
+    VelocityY,
+    Mass,
+    Charge,
+    FieldStrength,
+}
+
+impl Property {
+    fn apply(&self, data: &mut SceneEntityData, value: f32) -> Result<(), BatchError> {
+        let missing = |name: &str, component: &str| {
+            BatchError::InvalidArgument(format!("\"{}\" has no {}", name, component))
+        };
+        match self {
+            Property::X => data.transform.translation.x = value,
+            Property::Y => data.transform.translation.y = value,
+            Property::VelocityX | Property::VelocityY | Property::Mass => {
+                let rigid_body = data
+                    .rigid_body
+                    .as_mut()
+                    .ok_or_else(|| missing(&data.name, "rigid body"))?;
+                match self {
+                    Property::VelocityX => rigid_body.initial_velocity.x = value,
+                    Property::VelocityY => rigid_body.initial_velocity.y = value,
+                    _ => rigid_body.mass = Some(value),
+                }
+            }
+            Property::Charge => {
+                let charge = data
+                    .charge
+                    .as_mut()
+                    .ok_or_else(|| missing(&data.name, "charge"))?;
+                *charge = value;
+            }
+            Property::FieldStrength => {
+                let field = data
+                    .field
+                    .as_mut()
+                    .ok_or_else(|| missing(&data.name, "field"))?;
+                field.strength = value;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Property {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "x" => Ok(Property::X),
+            "y" => Ok(Property::Y),
+            "vx" => Ok(Property::VelocityX),
+            "vy" => Ok(Property::VelocityY),
+            "mass" => Ok(Property::Mass),
+            "charge" => Ok(Property::Charge),
+            "strength" => Ok(Property::FieldStrength),
+            _ => Err(format!(
+                "Unknown property \"{}\", expected one of x, y, vx, vy, mass, charge, strength",
+                s
+            )),
+        }
+    }
+}
+
+/// Values to try for one property, written as `<entity>.<property>=<values>`
+/// where the values are either a list (`1,2,5`) or `start..end:count`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SweepParameter {
+    pub entity: String,
+    pub property: Property,
+    pub values: Vec<f32>,
+}
+
+impl SweepParameter {
+    pub fn label(&self) -> String {
+        format!("{}.{}", self.entity, property_name(self.property))
+    }
+}
+
+fn property_name(property: Property) -> &'static str {
+    match property {
+        Property::X => "x",
+        Property::Y => "y",
+        Property::VelocityX => "vx",
+        Property::VelocityY => "vy",
+        Property::Mass => "mass",
+        Property::Charge => "charge",
+        Property::FieldStrength => "strength",
+    }
+}
+
+impl FromStr for SweepParameter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (target, values) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Sweep \"{}\" should be <entity>.<property>=<values>", s))?;
+        let (entity, property) = target
+            .trim()
+            .rsplit_once('.')
+            .ok_or_else(|| format!("Sweep \"{}\" should start with <entity>.<property>", s))?;
+        let parse = |value: &str| {
+            value
+                .trim()
+                .parse::<f32>()
+                .map_err(|_| format!("Invalid value \"{}\"", value.trim()))
+        };
+
+        let values = if let Some((range, count)) = values.split_once(':') {
+            let (start, end) = range
+                .split_once("..")
+                .ok_or_else(|| format!("Range \"{}\" should be start..end:count", values))?;
+            let (start, end) = (parse(start)?, parse(end)?);
+            let count: usize = count
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid count \"{}\"", count.trim()))?;
+            match count {
+                0 => Vec::new(),
+                1 => vec![start],
+                _ => (0..count)
+                    .map(|i| start + (end - start) * i as f32 / (count - 1) as f32)
+                    .collect(),
+            }
+        } else {
+            values.split(',').map(parse).collect::<Result<_, _>>()?
+        };
+        if values.is_empty() {
+            return Err(format!("Sweep \"{}\" has no values", s));
+        }
+
+        Ok(Self {
+            entity: entity.to_string(),
+            property: property.parse()?,
+            values,
+        })
+    }
+}
+
+/// Quantity of a named entity read at the end of every run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measure {
+    pub entity: String,
+    pub quantity: Quantity,
+    label: String,
+}
+
+impl Measure {
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl FromStr for Measure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (entity, quantity) = s
+            .trim()
+            .rsplit_once('.')
+            .ok_or_else(|| format!("Measure \"{}\" should be <entity>.<quantity>", s))?;
+        Ok(Self {
+            entity: entity.to_string(),
+            quantity: quantity.parse()?,
+            label: s.trim().to_string(),
+        })
+    }
+}
+
+/// Every combination of the parameter values, each run as its own batch.
+#[derive(Debug, Clone, Default)]
+pub struct Sweep {
+    pub parameters: Vec<SweepParameter>,
+    pub measures: Vec<Measure>,
+}
+
+impl Sweep {
+    /// Cartesian product of the parameter values, last parameter varying fastest.
+    pub fn combinations(&self) -> Vec<Vec<f32>> {
+        self.parameters
+            .iter()
+            .fold(vec![Vec::new()], |combinations, parameter| {
+                combinations
+                    .iter()
+                    .flat_map(|combination| {
+                        parameter.values.iter().map(move |value| {
+                            let mut combination = combination.clone();
+                            combination.push(*value);
+                            combination
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    fn scene_with(&self, scene: &SceneData, values: &[f32]) -> Result<SceneData, BatchError> {
+        let mut scene = scene.clone();
+        for (parameter, value) in self.parameters.iter().zip(values) {
+            let mut found = false;
+            for data in scene
+                .entities
+                .iter_mut()
+                .filter(|data| data.name == parameter.entity)
+            {
+                parameter.property.apply(data, *value)?;
+                found = true;
+            }
+            if !found {
+                return Err(BatchError::UnknownEntity(parameter.entity.clone()));
+            }
+        }
+        Ok(scene)
+    }
+}
+
+/// Outcome of one combination.
+#[derive(Debug, Clone)]
+pub struct SweepRow {
+    pub values: Vec<f32>,
+    pub steps: u64,
+    pub time: f32,
+    pub stopped: Option<StopReason>,
+    /// One per measure, `None` if the entity was gone by the end.
+    pub measured: Vec<Option<f32>>,
+}
+
+/// Runs every combination of `sweep` on up to `jobs` threads. Rows come back
+/// in the order of `Sweep::combinations`.
+pub fn run_sweep(
+    scene: &SceneData,
+    sweep: &Sweep,
+    config: &BatchConfig,
+    jobs: usize,
+) -> Result<Vec<SweepRow>, BatchError> {
+    let combinations = sweep.combinations();
+    if let Some(first) = combinations.first() {
+        sweep.scene_with(scene, first)?;
+    }
+    if let Some(measure) = sweep
+        .measures
+        .iter()
+        .find(|measure| !scene.entities.iter().any(|e| e.name == measure.entity))
+    {
+        return Err(BatchError::UnknownEntity(measure.entity.clone()));
+    }
+
+    // Only the final state is needed for the measures.
+    let config = BatchConfig {
+        sample_every: 0,
+        ..config.clone()
+    };
+    let next = AtomicUsize::new(0);
+    let rows = Mutex::new((0..combinations.len()).map(|_| None).collect::<Vec<_>>());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, combinations.len().max(1)) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(values) = combinations.get(index) else {
+                        break;
+                    };
+                    let row = run_combination(scene, sweep, &config, values);
+                    rows.lock().unwrap()[index] = Some(row);
+                }
+            });
+        }
+    });
+
+    rows.into_inner()
+        .unwrap()
+        .into_iter()
+        .map(|row| row.expect("every combination ran"))
+        .collect()
+}
+
+fn run_combination(
+    scene: &SceneData,
+    sweep: &Sweep,
+    config: &BatchConfig,
+    values: &[f32],
+) -> Result<SweepRow, BatchError> {
+    let scene = sweep.scene_with(scene, values)?;
+    let run = run_batch(&scene, config.clone())?;
+
+    let measured = sweep
+        .measures
+        .iter()
+        .map(|measure| {
+            run.samples
+                .iter()
+                .find(|sample| sample.name == measure.entity)
+                .map(|sample| measure.quantity.value(sample))
+        })
+        .collect();
+
+    Ok(SweepRow {
+        values: values.to_vec(),
+        steps: run.steps,
+        time: run.time,
+        stopped: run.stopped,
+        measured,
+    })
+}
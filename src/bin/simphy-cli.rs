@@ -0,0 +1,236 @@
+//! Runs a scene headless and writes each entity's trajectory as CSV or JSON, or with `--sweep`
+//! runs every combination of the swept values and writes one row per run.
+//!
+//! ```text
+//! simphy-cli <scene.yaml|scene.ron> [--steps N] [--duration SECONDS]
+//!            [--until ENTITY.QUANTITY<VALUE] [--interval SECONDS]
+//!            [--format csv|json] [--output FILE]
+//!            [--sweep ENTITY.PROPERTY=VALUES]... [--measure ENTITY.QUANTITY]...
+//! ```
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use simphy::batch::{
+    BatchConfig, BatchError, StopReason, Sweep, run_batch, run_sweep, write_sweep_csv,
+};
+use simphy::project::{TrajectoryFormat, import_scene, write_trajectories};
+
+const USAGE: &str = "\
+Usage: simphy-cli <scene.yaml|scene.ron> [options]
+
+Options:
+  --steps N           Stop after N physics steps (default 640)
+  --duration SECONDS  Stop after this much simulated time
+  --until CONDITION   Stop once CONDITION holds, e.g. \"Ball.y<0\"
+                      Quantities: x, y, vx, vy, speed, angle, omega
+  --interval SECONDS  Time between samples, 0 for the final state only (default: every step)
+  --format FORMAT     csv or json (default: from the output file, else csv)
+  --output FILE       Write to FILE instead of stdout
+
+Sweeps:
+  --sweep PARAMETER   Run once per value, e.g. \"Ball.charge=-10..10:5\" or \"Ball.vx=0,50,100\"
+                      Several sweeps run every combination
+                      Properties: x, y, vx, vy, mass, charge, strength
+  --measure QUANTITY  Record a quantity at the end of each run, e.g. \"Ball.x\"
+  --jobs N            Runs at once (default: number of CPUs)
+                      Sweeps always write CSV; --format and --interval don't apply";
+
+struct Args {
+    scene: PathBuf,
+    steps: Option<u64>,
+    duration: Option<f64>,
+    interval: Option<f64>,
+    format: Option<TrajectoryFormat>,
+    config: BatchConfig,
+    output: Option<PathBuf>,
+    sweep: Sweep,
+    jobs: Option<usize>,
+}
+
+fn parse_args() -> Result<Args, BatchError> {
+    let mut scene = None;
+    let mut steps = None;
+    let mut duration = None;
+    let mut interval = None;
+    let mut format = None;
+    let mut config = BatchConfig::default();
+    let mut output = None;
+    let mut sweep = Sweep::default();
+    let mut jobs = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| BatchError::InvalidArgument(format!("{} needs a value", arg)))
+        };
+        match arg.as_str() {
+            "--steps" => steps = Some(parse_number(&value()?)?),
+            "--duration" => duration = Some(parse_number(&value()?)?),
+            "--until" => {
+                config.until = Some(value()?.parse().map_err(BatchError::InvalidArgument)?)
+            }
+            "--interval" => interval = Some(parse_number(&value()?)?),
+            "--format" => {
+                format = Some(match value()?.as_str() {
+                    "csv" => TrajectoryFormat::Csv,
+                    "json" => TrajectoryFormat::Json,
+                    other => {
+                        return Err(BatchError::InvalidArgument(format!(
+                            "Unknown format \"{}\", expected csv or json",
+                            other
+                        )));
+                    }
+                })
+            }
+            "--output" | "-o" => output = Some(PathBuf::from(value()?)),
+            "--sweep" => sweep
+                .parameters
+                .push(value()?.parse().map_err(BatchError::InvalidArgument)?),
+            "--measure" => sweep
+                .measures
+                .push(value()?.parse().map_err(BatchError::InvalidArgument)?),
+            "--jobs" | "-j" => jobs = Some(parse_number(&value()?)?),
+            "--help" | "-h" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            _ if arg.starts_with('-') => {
+                return Err(BatchError::InvalidArgument(format!(
+                    "Unknown option {}",
+                    arg
+                )));
+            }
+            _ if scene.is_none() => scene = Some(PathBuf::from(arg)),
+            _ => {
+                return Err(BatchError::InvalidArgument(format!(
+                    "Unexpected argument {}",
+                    arg
+                )));
+            }
+        }
+    }
+
+    let scene =
+        scene.ok_or_else(|| BatchError::InvalidArgument("No scene file given".to_string()))?;
+    Ok(Args {
+        scene,
+        steps,
+        duration,
+        interval,
+        format,
+        config,
+        output,
+        sweep,
+        jobs,
+    })
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, BatchError> {
+    value
+        .parse()
+        .map_err(|_| BatchError::InvalidArgument(format!("Invalid number \"{}\"", value)))
+}
+
+fn run() -> Result<(), BatchError> {
+    let Args {
+        scene: path,
+        steps,
+        duration,
+        interval,
+        format,
+        mut config,
+        output,
+        sweep,
+        jobs,
+    } = parse_args()?;
+
+    // A sweep writes one CSV row per run, measured at its end.
+    if !sweep.parameters.is_empty() && (format.is_some() || interval.is_some()) {
+        return Err(BatchError::InvalidArgument(
+            "--format and --interval don't apply to --sweep, which writes a CSV table".to_string(),
+        ));
+    }
+
+    let scene = import_scene(&path)?;
+    let timestep_hz = scene.environment.physics.timestep_hz;
+    let duration_steps = duration.map(|seconds| (seconds * timestep_hz).ceil() as u64);
+    config.max_steps = match (steps, duration_steps) {
+        (Some(steps), Some(duration_steps)) => steps.min(duration_steps),
+        (Some(steps), None) => steps,
+        (None, Some(duration_steps)) => duration_steps,
+        (None, None) => config.max_steps,
+    };
+
+    if let Some(seconds) = interval {
+        // Intervals shorter than a step still record every step.
+        config.sample_every = if seconds <= 0.0 {
+            0
+        } else {
+            ((seconds * timestep_hz).round() as u64).max(1)
+        };
+    }
+    let format = match (format, &output) {
+        (Some(format), _) => format,
+        (None, Some(output)) => {
+            TrajectoryFormat::from_path(output).unwrap_or(TrajectoryFormat::Csv)
+        }
+        (None, None) => TrajectoryFormat::Csv,
+    };
+
+    let mut writer: Box<dyn Write> = match output {
+        Some(output) => Box::new(BufWriter::new(File::create(output)?)),
+        None => Box::new(std::io::stdout().lock()),
+    };
+
+    if !sweep.parameters.is_empty() {
+        let jobs = jobs
+            .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |jobs| jobs.get()));
+        let rows = run_sweep(&scene, &sweep, &config, jobs)?;
+        write_sweep_csv(&sweep, &rows, &mut writer)?;
+        writer.flush()?;
+        eprintln!("Ran {} combinations", rows.len());
+        return Ok(());
+    }
+
+    let interval = (config.sample_every as f64 / timestep_hz) as f32;
+    let run = run_batch(&scene, config)?;
+    write_trajectories(&run.trajectories(interval), format, &mut writer)?;
+    writer.flush()?;
+
+    let reason = match run.stopped {
+        Some(StopReason::Condition) => "stop condition reached",
+        _ => "step limit reached",
+    };
+    eprintln!(
+        "Stopped after {} steps, t = {:.3} s: {}",
+        run.steps, run.time, reason
+    );
+    let tolerance = scene.environment.conservation.tolerance;
+    if let Some(conservation) = &run.conservation
+        && conservation.flagged(tolerance)
+    {
+        eprintln!(
+            "warning: conservation drift above {:.1}% (energy {:.2}%, momentum {:.2}%, angular momentum {:.2}%)",
+            tolerance * 100.0,
+            conservation.energy_drift * 100.0,
+            conservation.momentum_drift * 100.0,
+            conservation.angular_momentum_drift * 100.0,
+        );
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            eprintln!("{}", USAGE);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -13,10 +13,11 @@ pub struct PlacementIndicator {
     pub size: Vec2,
 }
 
-#[derive(Component)]
+/// Editor-side data the collider alone can't give back, such as the size the
+/// shape was generated from.
+#[derive(Component, Clone, Copy)]
 pub struct PlacedEntity {
-    pub _bounds: Rect,
-    pub shape: EntityShape,
+    pub size: Vec2,
     pub color: Color,
 }
 
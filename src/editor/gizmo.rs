@@ -0,0 +1,181 @@
+use avian2d::prelude::Collider;
+use bevy::prelude::*;
+
+use super::components::{EditorEntity, PlacedEntity};
+use super::history::{EditCommand, EditHistory};
+use super::resources::{GizmoHandle, GizmoState, PlacementState, SelectionState, Tool};
+use crate::camera::WorldCamera;
+use crate::project::SceneEntityQuery;
+use crate::settings::editor_prefs::EditorPrefs;
+use crate::shared::{EntityShape, WorldMousePosition};
+use crate::ui::GameViewTab;
+
+/// Handle size on screen, in pixels at a camera scale of 1.
+pub const HANDLE_RADIUS: f32 = 6.0;
+
+const ROTATION_SNAP: f32 = std::f32::consts::PI / 12.0;
+const MIN_SIZE: f32 = 1.0;
+
+pub const EDGES: [Vec2; 4] = [Vec2::X, Vec2::NEG_X, Vec2::Y, Vec2::NEG_Y];
+
+pub fn rotation_of(transform: &Transform) -> f32 {
+    transform.rotation.to_euler(EulerRot::ZYX).0
+}
+
+/// Radius of the rotate ring around an entity of `size`.
+pub fn ring_radius(size: Vec2, scale: f32) -> f32 {
+    size.length() / 2.0 + 4.0 * HANDLE_RADIUS * scale
+}
+
+/// World position of the handle on `edge` of an entity of `size`.
+pub fn edge_handle(transform: &Transform, size: Vec2, edge: Vec2) -> Vec2 {
+    let local = edge * size / 2.0;
+    transform.translation.truncate() + Vec2::from_angle(rotation_of(transform)).rotate(local)
+}
+
+/// Handle of the gizmo under `point`, if any.
+fn handle_at(
+    tool: Tool,
+    transform: &Transform,
+    size: Vec2,
+    point: Vec2,
+    scale: f32,
+) -> Option<GizmoHandle> {
+    let tolerance = 2.0 * HANDLE_RADIUS * scale;
+    match tool {
+        Tool::Rotate => {
+            let distance = point.distance(transform.translation.truncate());
+            ((distance - ring_radius(size, scale)).abs() < tolerance).then_some(GizmoHandle::Ring)
+        }
+        Tool::Scale => EDGES
+            .into_iter()
+            .find(|edge| edge_handle(transform, size, *edge).distance(point) < tolerance)
+            .map(GizmoHandle::Edge),
+        _ => None,
+    }
+}
+
+/// Rotates and resizes the primary selection by its gizmo handles. The
+/// collider is regenerated from the new size while dragging.
+pub fn transform_gizmo(
+    placement: Res<PlacementState>,
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    world_mouse: Res<WorldMousePosition>,
+    selection: Res<SelectionState>,
+    editor_prefs: Res<EditorPrefs>,
+    camera: Single<&Transform, (With<WorldCamera>, Without<EditorEntity>)>,
+    mut gizmo: ResMut<GizmoState>,
+    mut history: ResMut<EditHistory>,
+    mut queries: ParamSet<(
+        Query<
+            (
+                &mut Transform,
+                &mut PlacedEntity,
+                &EntityShape,
+                &mut Collider,
+            ),
+            With<EditorEntity>,
+        >,
+        Query<SceneEntityQuery, With<EditorEntity>>,
+    )>,
+    game_view_tab: Res<GameViewTab>,
+) {
+    if !matches!(placement.tool, Tool::Rotate | Tool::Scale) {
+        gizmo.handle = None;
+        return;
+    }
+
+    if mouse_button.just_pressed(MouseButton::Left)
+        && game_view_tab.mouse_in
+        && let (Some(entity), Some(pos)) = (selection.selected_entity, world_mouse.position)
+        && let Ok(item) = queries.p1().get(entity)
+        && let Some(placed) = item.placed
+        && let Some(handle) = handle_at(
+            placement.tool,
+            item.transform,
+            placed.size,
+            pos,
+            camera.scale.x,
+        )
+    {
+        gizmo.entity = Some(entity);
+        gizmo.handle = Some(handle);
+        gizmo.start = pos;
+        gizmo.before = Some(item.to_data());
+    }
+
+    if mouse_button.pressed(MouseButton::Left)
+        && let (Some(entity), Some(handle), Some(before), Some(pos)) = (
+            gizmo.entity,
+            gizmo.handle,
+            gizmo.before.as_ref(),
+            world_mouse.position,
+        )
+        && let Ok((mut transform, mut placed, shape, mut collider)) = queries.p0().get_mut(entity)
+    {
+        let center = before.transform.translation.truncate();
+        let rotation = before.transform.rotation;
+        match handle {
+            GizmoHandle::Ring => {
+                let mut angle = rotation + (gizmo.start - center).angle_to(pos - center);
+                if editor_prefs.snap_rotation {
+                    angle = (angle / ROTATION_SNAP).round() * ROTATION_SNAP;
+                }
+                transform.rotation = Quat::from_rotation_z(angle);
+            }
+            GizmoHandle::Edge(edge) => {
+                let size = before
+                    .collider
+                    .map(|collider| collider.size)
+                    .unwrap_or(placed.size);
+                let axis_size = edge.abs().dot(size);
+                let local = Vec2::from_angle(-rotation).rotate(pos - center);
+                let (new_size, new_center) = if shape.is_uniform() {
+                    // Grows on both axes at once, so it scales about its centre.
+                    let new_axis_size = (2.0 * local.dot(edge)).max(MIN_SIZE);
+                    (Vec2::splat(new_axis_size), center)
+                } else {
+                    // The opposite edge stays where it is.
+                    let new_axis_size = (local.dot(edge) + axis_size / 2.0).max(MIN_SIZE);
+                    let shift = edge * (new_axis_size - axis_size) / 2.0;
+                    (
+                        size + edge.abs() * (new_axis_size - axis_size),
+                        center + Vec2::from_angle(rotation).rotate(shift),
+                    )
+                };
+
+                transform.translation.x = new_center.x;
+                transform.translation.y = new_center.y;
+                if placed.size != new_size {
+                    placed.size = new_size;
+                    *collider = shape.to_collider(new_size);
+                }
+            }
+        }
+    }
+
+    if mouse_button.just_released(MouseButton::Left)
+        && let (Some(entity), Some(handle), Some(before)) =
+            (gizmo.entity, gizmo.handle.take(), gizmo.before.take())
+        && let Ok(item) = queries.p1().get(entity)
+    {
+        let after = item.to_data();
+        let label = match handle {
+            GizmoHandle::Ring => "Rotate",
+            GizmoHandle::Edge(_) => "Resize",
+        };
+        if after.transform.rotation != before.transform.rotation
+            || after.collider.map(|collider| collider.size)
+                != before.collider.map(|collider| collider.size)
+        {
+            history.record(
+                label,
+                EditCommand::Modify {
+                    entity,
+                    before,
+                    after,
+                },
+            );
+        }
+    }
+}
@@ -0,0 +1,381 @@
+use std::collections::VecDeque;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::resources::{PlacedEntities, SelectionState};
+use crate::project::file_format::EnvironmentData;
+use crate::project::{Document, SceneEntityData, insert_scene_entity, spawn_scene_entity};
+use crate::simulation::SimulationSettings;
+
+/// A reversible change to the scene. Entities are stored as they were when
+/// the command last ran; undoing a deletion respawns the entity under a new
+/// id, and the history is remapped to it.
+#[derive(Clone)]
+pub enum EditCommand {
+    Spawn {
+        entity: Entity,
+        data: SceneEntityData,
+    },
+    Despawn {
+        entity: Entity,
+        data: SceneEntityData,
+    },
+    Modify {
+        entity: Entity,
+        before: SceneEntityData,
+        after: SceneEntityData,
+    },
+    /// Several commands undone and redone as one step.
+    Group(Vec<EditCommand>),
+}
+
+impl EditCommand {
+    /// Groups `commands`, leaving a lone command as it is.
+    pub fn group(mut commands: Vec<EditCommand>) -> Self {
+        if commands.len() == 1 {
+            commands.remove(0)
+        } else {
+            EditCommand::Group(commands)
+        }
+    }
+
+    fn inverse(self) -> Self {
+        match self {
+            EditCommand::Spawn { entity, data } => EditCommand::Despawn { entity, data },
+            EditCommand::Despawn { entity, data } => EditCommand::Spawn { entity, data },
+            EditCommand::Modify {
+                entity,
+                before,
+                after,
+            } => EditCommand::Modify {
+                entity,
+                before: after,
+                after: before,
+            },
+            EditCommand::Group(commands) => {
+                EditCommand::Group(commands.into_iter().rev().map(Self::inverse).collect())
+            }
+        }
+    }
+
+    fn remap(&mut self, from: Entity, to: Entity) {
+        match self {
+            EditCommand::Spawn { entity, .. }
+            | EditCommand::Despawn { entity, .. }
+            | EditCommand::Modify { entity, .. } => {
+                if *entity == from {
+                    *entity = to;
+                }
+            }
+            EditCommand::Group(commands) => {
+                for command in commands {
+                    command.remap(from, to);
+                }
+            }
+        }
+    }
+
+    fn modifies_same(&self, other: &EditCommand) -> bool {
+        matches!(
+            (self, other),
+            (EditCommand::Modify { entity, .. }, EditCommand::Modify { entity: other, .. })
+                if entity == other
+        )
+    }
+
+    /// Continues a modification with `next` if both touch the same entities.
+    fn merge(&mut self, next: &EditCommand) -> bool {
+        match (self, next) {
+            (
+                EditCommand::Modify { entity, after, .. },
+                EditCommand::Modify {
+                    entity: next_entity,
+                    after: next_after,
+                    ..
+                },
+            ) if entity == next_entity => {
+                *after = next_after.clone();
+                true
+            }
+            (EditCommand::Group(commands), EditCommand::Group(next_commands))
+                if commands.len() == next_commands.len()
+                    && commands
+                        .iter()
+                        .zip(next_commands)
+                        .all(|(command, next)| command.modifies_same(next)) =>
+            {
+                for (command, next) in commands.iter_mut().zip(next_commands) {
+                    command.merge(next);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+struct HistoryEntry {
+    id: u64,
+    label: &'static str,
+    command: EditCommand,
+}
+
+enum PendingEdit {
+    Execute {
+        label: &'static str,
+        command: EditCommand,
+        merge: bool,
+    },
+    Undo,
+    Redo,
+}
+
+/// Undo and redo stacks of editor operations.
+///
+/// Systems that already changed the world call `record`; the UI calls
+/// `execute`, `undo` and `redo`, which `apply_history` carries out.
+#[derive(Resource)]
+pub struct EditHistory {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+    pending: Vec<PendingEdit>,
+    next_id: u64,
+    /// Entry on top of the undo stack when the document was last saved.
+    saved: Option<u64>,
+    /// Oldest entries are dropped past this many.
+    pub limit: usize,
+}
+
+impl Default for EditHistory {
+    fn default() -> Self {
+        Self {
+            undo: VecDeque::new(),
+            redo: Vec::new(),
+            pending: Vec::new(),
+            next_id: 0,
+            saved: None,
+            limit: 200,
+        }
+    }
+}
+
+impl EditHistory {
+    /// Adds a command whose effect is already in the world.
+    pub fn record(&mut self, label: &'static str, command: EditCommand) {
+        self.push(label, command, false);
+    }
+
+    /// Carries out `command` on the next update and records it. With `merge`,
+    /// a modification of the same entity continues the previous entry, so a
+    /// drag on an inspector value undoes in one step.
+    pub fn execute(&mut self, label: &'static str, command: EditCommand, merge: bool) {
+        self.pending.push(PendingEdit::Execute {
+            label,
+            command,
+            merge,
+        });
+    }
+
+    pub fn undo(&mut self) {
+        self.pending.push(PendingEdit::Undo);
+    }
+
+    pub fn redo(&mut self) {
+        self.pending.push(PendingEdit::Redo);
+    }
+
+    pub fn undo_label(&self) -> Option<&'static str> {
+        self.undo.back().map(|entry| entry.label)
+    }
+
+    pub fn redo_label(&self) -> Option<&'static str> {
+        self.redo.last().map(|entry| entry.label)
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.pending.clear();
+        self.saved = None;
+    }
+
+    pub fn mark_saved(&mut self) {
+        self.saved = self.undo.back().map(|entry| entry.id);
+    }
+
+    /// Whether the scene differs from the one last saved or opened.
+    pub fn is_dirty(&self) -> bool {
+        self.undo.back().map(|entry| entry.id) != self.saved
+    }
+
+    /// Points every entry at `to` after `from` was respawned.
+    pub fn remap(&mut self, from: Entity, to: Entity) {
+        for entry in self.undo.iter_mut().chain(self.redo.iter_mut()) {
+            entry.command.remap(from, to);
+        }
+    }
+
+    fn push(&mut self, label: &'static str, command: EditCommand, merge: bool) {
+        self.redo.clear();
+
+        if merge
+            && let Some(top) = self.undo.back_mut()
+            && top.label == label
+            && Some(top.id) != self.saved
+            && top.command.merge(&command)
+        {
+            return;
+        }
+
+        self.undo.push_back(HistoryEntry {
+            id: self.next_id,
+            label,
+            command,
+        });
+        self.next_id += 1;
+        if self.undo.len() > self.limit {
+            self.undo.pop_front();
+        }
+    }
+}
+
+/// The open document together with its edit history.
+#[derive(SystemParam)]
+pub struct DocumentHistory<'w> {
+    pub document: ResMut<'w, Document>,
+    pub history: ResMut<'w, EditHistory>,
+}
+
+/// Makes `command` take effect, returning it with the entity it now refers to.
+fn apply(
+    commands: &mut Commands,
+    placed_entities: &mut PlacedEntities,
+    selection: &mut SelectionState,
+    history: &mut EditHistory,
+    mut command: EditCommand,
+) -> EditCommand {
+    match &mut command {
+        EditCommand::Spawn { entity, data } => {
+            let spawned = spawn_scene_entity(commands, data);
+            placed_entities.entities.push(spawned);
+            history.remap(*entity, spawned);
+            *entity = spawned;
+        }
+        EditCommand::Despawn { entity, .. } => {
+            if let Ok(mut entity_commands) = commands.get_entity(*entity) {
+                entity_commands.despawn();
+            }
+            placed_entities.entities.retain(|e| e != entity);
+            selection.remove(*entity);
+        }
+        EditCommand::Modify { entity, after, .. } => {
+            if let Ok(mut entity_commands) = commands.get_entity(*entity) {
+                insert_scene_entity(&mut entity_commands, after);
+            }
+        }
+        EditCommand::Group(group) => {
+            for command in group.iter_mut() {
+                *command = apply(
+                    commands,
+                    placed_entities,
+                    selection,
+                    history,
+                    std::mem::replace(command, EditCommand::Group(Vec::new())),
+                );
+            }
+        }
+    }
+    command
+}
+
+fn spawned_entities(command: &EditCommand, entities: &mut Vec<Entity>) {
+    match command {
+        EditCommand::Spawn { entity, .. } => entities.push(*entity),
+        EditCommand::Group(commands) => {
+            for command in commands {
+                spawned_entities(command, entities);
+            }
+        }
+        _ => {}
+    }
+}
+
+pub fn apply_history(
+    mut commands: Commands,
+    mut history: ResMut<EditHistory>,
+    mut placed_entities: ResMut<PlacedEntities>,
+    mut selection: ResMut<SelectionState>,
+) {
+    if history.pending.is_empty() {
+        return;
+    }
+
+    for pending in std::mem::take(&mut history.pending) {
+        match pending {
+            PendingEdit::Execute {
+                label,
+                command,
+                merge,
+            } => {
+                let command = apply(
+                    &mut commands,
+                    &mut placed_entities,
+                    &mut selection,
+                    &mut history,
+                    command,
+                );
+                let mut spawned = Vec::new();
+                spawned_entities(&command, &mut spawned);
+                if !spawned.is_empty() {
+                    selection.clear();
+                    selection.extend(spawned);
+                }
+                history.push(label, command, merge);
+            }
+            PendingEdit::Undo => {
+                let Some(entry) = history.undo.pop_back() else {
+                    continue;
+                };
+                let command = apply(
+                    &mut commands,
+                    &mut placed_entities,
+                    &mut selection,
+                    &mut history,
+                    entry.command.inverse(),
+                );
+                history.redo.push(HistoryEntry {
+                    command: command.inverse(),
+                    ..entry
+                });
+            }
+            PendingEdit::Redo => {
+                let Some(entry) = history.redo.pop() else {
+                    continue;
+                };
+                let command = apply(
+                    &mut commands,
+                    &mut placed_entities,
+                    &mut selection,
+                    &mut history,
+                    entry.command,
+                );
+                history.undo.push_back(HistoryEntry { command, ..entry });
+            }
+        }
+    }
+}
+
+/// Keeps `Document::is_dirty` in step with the position in the history and
+/// with the environment settings, which are edited outside of it.
+pub fn sync_document_dirty(
+    history: Res<EditHistory>,
+    settings: SimulationSettings,
+    mut document: ResMut<Document>,
+) {
+    let is_dirty = history.is_dirty()
+        || EnvironmentData::from_settings(&settings) != document.saved_environment;
+    if document.is_dirty != is_dirty {
+        document.is_dirty = is_dirty;
+    }
+}
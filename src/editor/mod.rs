@@ -1,10 +1,17 @@
 pub mod components;
+pub mod gizmo;
+pub mod history;
+pub mod picking;
 pub mod plugin;
 pub mod resources;
+pub mod snapping;
 
 pub use components::{EditorEntity, EntityName, PlacementIndicator, PlacedEntity, SelectedEntity};
+pub use history::{DocumentHistory, EditCommand, EditHistory};
+pub use picking::{EntityPicker, PICK_RADIUS, PickQuery, entities_in_box};
 pub use plugin::EditorPlugin;
 pub use resources::{
-    ClearRequest, EntityProperties, FieldType, IndicatorInfo, PlacementState, PlacedEntities,
-    RigidBodyType, SelectionState, Tool,
+    BoxSelectState, ClearRequest, EntityProperties, FieldType, GizmoHandle, GizmoState,
+    IndicatorInfo, PlacementState, PlacedEntities, RigidBodyType, SelectionState, SnapGuides,
+    Tool,
 };
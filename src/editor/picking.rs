@@ -0,0 +1,126 @@
+use avian2d::prelude::*;
+use bevy::ecs::entity::EntityHashSet;
+use bevy::ecs::query::QueryData;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use super::components::EditorEntity;
+use super::gizmo::rotation_of;
+
+#[derive(QueryData)]
+pub struct PickQuery {
+    pub entity: Entity,
+    pub transform: &'static Transform,
+    pub collider: &'static Collider,
+    pub sensor: Has<Sensor>,
+}
+
+impl PickQueryItem<'_, '_> {
+    fn rotation(&self) -> Rotation {
+        Rotation::radians(rotation_of(self.transform))
+    }
+
+    pub fn aabb(&self) -> ColliderAabb {
+        self.collider
+            .aabb(self.transform.translation.truncate(), self.rotation())
+    }
+
+    pub fn bounds(&self) -> Rect {
+        let aabb = self.aabb();
+        Rect::from_corners(aabb.min, aabb.max)
+    }
+
+    fn area(&self) -> f32 {
+        let aabb = self.aabb();
+        let size = aabb.max - aabb.min;
+        size.x * size.y
+    }
+}
+
+/// Screen distance, in pixels at a camera scale of 1, within which a click
+/// still hits a collider, so that tiny particles stay selectable.
+pub const PICK_RADIUS: f32 = 6.0;
+
+/// Spatial queries against the editor entities' colliders.
+#[derive(SystemParam)]
+pub struct EntityPicker<'w, 's> {
+    spatial_query: SpatialQuery<'w, 's>,
+    items: Query<'w, 's, PickQuery, With<EditorEntity>>,
+}
+
+impl EntityPicker<'_, '_> {
+    /// Editor entities whose collider lies within `radius` of `point`,
+    /// ordered by preference: bodies before field sensors, shapes containing
+    /// the point before near misses, then smaller shapes before larger ones.
+    pub fn entities_at(&self, point: Vec2, radius: f32) -> Vec<Entity> {
+        let filter = SpatialQueryFilter::default();
+        let exact: EntityHashSet = self
+            .spatial_query
+            .point_intersections(point, &filter)
+            .into_iter()
+            .collect();
+        let mut hits: Vec<_> = self
+            .spatial_query
+            .shape_intersections(&Collider::circle(radius), point, 0.0, &filter)
+            .into_iter()
+            .filter_map(|entity| self.items.get(entity).ok())
+            .map(|item| {
+                let near_miss = !exact.contains(&item.entity);
+                (item.sensor, near_miss, item.area(), item.entity)
+            })
+            .collect();
+        hits.sort_by(|a, b| {
+            a.0.cmp(&b.0)
+                .then(a.1.cmp(&b.1))
+                .then(a.2.total_cmp(&b.2))
+                .then(a.3.cmp(&b.3))
+        });
+        hits.into_iter().map(|(.., entity)| entity).collect()
+    }
+}
+
+/// Avian only updates its spatial query pipeline while the simulation steps,
+/// so entities placed or moved while paused are caught up before a click is
+/// picked.
+pub fn refresh_spatial_query(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    physics_time: Res<Time<Physics>>,
+    mut params: ParamSet<(
+        Query<(&Transform, &mut Position, &mut Rotation), With<EditorEntity>>,
+        SpatialQuery,
+    )>,
+) {
+    if !mouse_button.just_pressed(MouseButton::Left) || !physics_time.is_paused() {
+        return;
+    }
+    for (transform, mut position, mut rotation) in &mut params.p0() {
+        position.set_if_neq(Position(transform.translation.truncate()));
+        rotation.set_if_neq(Rotation::radians(rotation_of(transform)));
+    }
+    params.p1().update_pipeline();
+}
+
+/// Picks among overlapping `hits`. Clicking the current entity again moves to
+/// the next hit when `cycle` is set, otherwise keeps it.
+pub fn pick(hits: &[Entity], current: Option<Entity>, cycle: bool) -> Option<Entity> {
+    let index = current.and_then(|current| hits.iter().position(|hit| *hit == current));
+    match index {
+        Some(index) if cycle => hits.get((index + 1) % hits.len()).copied(),
+        Some(index) => hits.get(index).copied(),
+        None => hits.first().copied(),
+    }
+}
+
+/// Editor entities whose centre lies inside the box spanned by `a` and `b`.
+pub fn entities_in_box(
+    a: Vec2,
+    b: Vec2,
+    query: &Query<PickQuery, With<EditorEntity>>,
+) -> Vec<Entity> {
+    let rect = Rect::from_corners(a, b);
+    query
+        .iter()
+        .filter(|item| rect.contains(item.transform.translation.truncate()))
+        .map(|item| item.entity)
+        .collect()
+}
@@ -1,14 +1,20 @@
-use avian2d::prelude::*;
 use bevy::prelude::*;
 
-use super::components::{EditorEntity, EntityName, PlacementIndicator, SelectedEntity};
+use super::components::{EditorEntity, PlacementIndicator, SelectedEntity};
+use super::gizmo::transform_gizmo;
+use super::history::{EditCommand, EditHistory, apply_history, sync_document_dirty};
+use super::picking::{
+    EntityPicker, PICK_RADIUS, PickQuery, entities_in_box, pick, refresh_spatial_query,
+};
 use super::resources::{
-    DragState, EntityProperties, PlacedEntities, PlacementState, RigidBodyType, SelectionState,
-    Tool,
+    BoxSelectState, DragState, DraggedEntity, EntityProperties, GizmoState, IndicatorInfo,
+    PlacedEntities, PlacementState, RigidBodyType, SelectionState, SnapGuides, Tool,
 };
-use crate::editor::FieldType;
-use crate::shared::{EntityShape, InitialState, WorldMousePosition};
-use crate::simulation::components::{Electric, Field, Magnetic};
+use super::snapping::{GUIDE_TOLERANCE, SNAP_OVERRIDE_KEYS, snap_position};
+use crate::camera::WorldCamera;
+use crate::project::{SceneEntityQuery, spawn_scene_entity};
+use crate::settings::editor_prefs::EditorPrefs;
+use crate::shared::{EntityShape, WorldMousePosition};
 use crate::ui::GameViewTab;
 
 pub struct EditorPlugin;
@@ -20,18 +26,29 @@ impl Plugin for EditorPlugin {
             .init_resource::<EntityProperties>()
             .init_resource::<PlacedEntities>()
             .init_resource::<DragState>()
+            .init_resource::<BoxSelectState>()
+            .init_resource::<GizmoState>()
+            .init_resource::<IndicatorInfo>()
+            .init_resource::<SnapGuides>()
+            .init_resource::<EditHistory>()
             .add_systems(
                 Update,
                 (
                     spawn_placement_indicator,
-                    update_placement_indicator,
-                    place_entity,
-                    select_entity,
-                    drag_entity,
+                    (update_placement_indicator, place_entity).chain(),
+                    (
+                        refresh_spatial_query,
+                        transform_gizmo,
+                        select_entity,
+                        drag_entity,
+                        finish_box_select,
+                    )
+                        .chain(),
                     update_entity_properties,
                     update_selected_entity_visual,
                 ),
-            );
+            )
+            .add_systems(Update, (apply_history, sync_document_dirty).chain());
     }
 }
 
@@ -53,25 +70,51 @@ fn spawn_placement_indicator(
     }
 }
 
+/// Snaps the pointer for the Place tool and moves the indicator there.
 fn update_placement_indicator(
-    mut indicator_query: Query<(&mut PlacementIndicator, &mut Transform), With<PlacementIndicator>>,
+    mut indicator_query: Query<
+        (&mut PlacementIndicator, &mut Transform),
+        (With<PlacementIndicator>, Without<EditorEntity>),
+    >,
     placement: Res<PlacementState>,
     world_mouse: Res<WorldMousePosition>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    editor_prefs: Res<EditorPrefs>,
+    camera: Single<&Transform, (With<WorldCamera>, Without<PlacementIndicator>)>,
+    pick_query: Query<PickQuery, (With<EditorEntity>, Without<PlacementIndicator>)>,
+    mut indicator_info: ResMut<IndicatorInfo>,
+    mut guides: ResMut<SnapGuides>,
     game_view_tab: Res<GameViewTab>,
 ) {
     if placement.tool != Tool::Place {
         return;
     }
+    guides.lines.clear();
 
     if let Some(pos) = world_mouse.position {
         if game_view_tab.mouse_in {
+            indicator_info.world_position = pos;
+            indicator_info.aligned_position = if keyboard.any_pressed(SNAP_OVERRIDE_KEYS) {
+                pos
+            } else {
+                let targets: Vec<_> = pick_query.iter().map(|item| item.bounds()).collect();
+                snap_position(
+                    pos,
+                    Rect::from_center_size(Vec2::ZERO, placement.size),
+                    &targets,
+                    &editor_prefs,
+                    GUIDE_TOLERANCE * camera.scale.x,
+                    &mut guides.lines,
+                )
+            };
+
             for (mut indicator, mut transform) in indicator_query.iter_mut() {
                 if indicator.shape != placement.shape || indicator.size != placement.size {
                     indicator.shape = placement.shape;
                     indicator.size = placement.size;
                 }
-                transform.translation.x = pos.x;
-                transform.translation.y = pos.y;
+                transform.translation.x = indicator_info.aligned_position.x;
+                transform.translation.y = indicator_info.aligned_position.y;
             }
         }
     }
@@ -82,7 +125,9 @@ fn place_entity(
     placement: Res<PlacementState>,
     mouse_button: Res<ButtonInput<MouseButton>>,
     world_mouse: Res<WorldMousePosition>,
+    indicator_info: Res<IndicatorInfo>,
     mut placed_entities: ResMut<PlacedEntities>,
+    mut history: ResMut<EditHistory>,
     game_view_tab: Res<GameViewTab>,
 ) {
     if placement.tool != Tool::Place {
@@ -93,144 +138,214 @@ fn place_entity(
         && game_view_tab.mouse_in
         && world_mouse.position.is_some()
     {
-        let size = placement.size;
-
-        let collider = placement.shape.to_collider(size);
-
-        let transform = Transform::from_translation(world_mouse.position.unwrap().extend(0.0));
+        let transform = Transform::from_translation(indicator_info.aligned_position.extend(0.0));
 
         // [todo] 输入的世界坐标不可能应用
 
-        let mut entity_commands = commands.spawn((
-            EditorEntity,
-            EntityName(format!(
-                "{} {}",
-                placement.shape.display_name(),
-                placed_entities.entities.len() + 1
-            )),
-            transform,
-            collider,
-            placement.charge,
-            placement.rigid_body,
-            placement.shape,
-            placement.initial_velocity,
-            placement.friction,
-            placement.constant_force.to_owned(),
-            InitialState::new(placement.transform, transform.translation.truncate()),
-        ));
-
-        if placement.field_type != FieldType::None {
-            entity_commands.insert(Sensor);
-            entity_commands.insert(placement.field);
-            entity_commands.insert(CollisionEventsEnabled);
-            entity_commands.insert(CollidingEntities::default());
-            match placement.field_type {
-                FieldType::Magnetic => {
-                    entity_commands.insert(Magnetic);
-                }
-                FieldType::Electric => {
-                    entity_commands.insert(Electric);
-                }
-                _ => {}
-            }
-        } else {
-            // [IMPORTANT] 场不能有Mass
-            entity_commands.insert(Mass(placement.mass));
-        }
-
-        let entity = entity_commands.id();
+        let name = format!(
+            "{} {}",
+            placement.shape.display_name(),
+            placed_entities.entities.len() + 1
+        );
+        let data = placement.entity_data(name, transform);
+        let entity = spawn_scene_entity(&mut commands, &data);
 
         placed_entities.entities.push(entity);
+        history.record("Place", EditCommand::Spawn { entity, data });
     }
 }
 
 fn select_entity(
     mouse_button: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
     world_mouse: Res<WorldMousePosition>,
     placement: Res<PlacementState>,
     mut selection: ResMut<SelectionState>,
+    mut box_select: ResMut<BoxSelectState>,
+    gizmo: Res<GizmoState>,
     mut properties: ResMut<EntityProperties>,
     mut commands: Commands,
     mut placed_entities: ResMut<PlacedEntities>,
-    mut document: ResMut<crate::project::Document>,
+    mut history: ResMut<EditHistory>,
     query: Query<(Entity, &Transform, &EntityShape), With<EditorEntity>>,
+    picker: EntityPicker,
+    scene_query: Query<SceneEntityQuery, With<EditorEntity>>,
+    camera: Single<&Transform, (With<WorldCamera>, Without<EditorEntity>)>,
     game_view_tab: Res<GameViewTab>,
 ) {
+    // A press on a gizmo handle belongs to the gizmo, not the selection.
+    if gizmo.handle.is_some() {
+        return;
+    }
+
     if mouse_button.just_pressed(MouseButton::Left) && game_view_tab.mouse_in {
         if let Some(world_pos) = world_mouse.position {
-            let mut closest_entity: Option<Entity> = None;
-            let mut closest_dist = f32::MAX;
+            let additive = keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]);
+            let hits = picker.entities_at(world_pos, PICK_RADIUS * camera.scale.x);
 
-            for (entity, transform, _shape) in query.iter() {
-                let dist = (transform.translation.truncate() - world_pos).length();
-                let radius = 25.0;
-
-                if dist < radius && dist < closest_dist {
-                    closest_dist = dist;
-                    closest_entity = Some(entity);
+            if placement.tool == Tool::Delete {
+                // Deletes the selected entity when it is among the hits, so
+                // that one cycled to with Select can be removed.
+                if let Some(entity) = pick(&hits, selection.selected_entity, false) {
+                    if let Ok(item) = scene_query.get(entity) {
+                        let data = item.to_data();
+                        history.record("Delete", EditCommand::Despawn { entity, data });
+                        commands.entity(entity).despawn();
+                        placed_entities.entities.retain(|e| *e != entity);
+                    }
                 }
-            }
-
-            if let Some(entity) = closest_entity {
-                if placement.tool == Tool::Delete {
-                    commands.entity(entity).despawn();
-                    placed_entities.entities.retain(|e| *e != entity);
-                    selection.selected_entity = None;
-                    properties.entity = None;
-                    document.is_dirty = true;
-                } else {
+                selection.clear();
+            } else if additive {
+                if let Some(&entity) = hits.first() {
+                    selection.toggle(entity);
+                } else if placement.tool == Tool::Select {
+                    box_select.start = Some(world_pos);
+                    box_select.additive = true;
+                }
+            } else if let Some(entity) = pick(
+                &hits,
+                selection.selected_entity,
+                placement.tool == Tool::Select,
+            ) {
+                // Grabbing any member of a group moves the whole group.
+                if placement.tool == Tool::Move && selection.contains(entity) {
                     selection.selected_entity = Some(entity);
-                    if let Ok((_entity, transform, shape)) = query.get(entity) {
-                        properties.entity = Some(entity);
-                        properties.position = transform.translation;
-                        properties.scale = Vec2::ONE * 50.0;
-                        properties.shape = *shape;
-                        properties.rigid_body_type = RigidBodyType::Dynamic;
-                    }
+                } else {
+                    selection.select(entity);
                 }
             } else {
-                selection.selected_entity = None;
-                properties.entity = None;
+                selection.clear();
+                if placement.tool == Tool::Select {
+                    box_select.start = Some(world_pos);
+                    box_select.additive = false;
+                }
+            }
+
+            properties.entity = None;
+            if let Some(entity) = selection.selected_entity
+                && let Ok((_entity, transform, shape)) = query.get(entity)
+            {
+                properties.entity = Some(entity);
+                properties.position = transform.translation;
+                properties.scale = Vec2::ONE * 50.0;
+                properties.shape = *shape;
+                properties.rigid_body_type = RigidBodyType::Dynamic;
             }
         }
     }
 }
 
+fn finish_box_select(
+    mouse_button: Res<ButtonInput<MouseButton>>,
+    world_mouse: Res<WorldMousePosition>,
+    mut box_select: ResMut<BoxSelectState>,
+    mut selection: ResMut<SelectionState>,
+    pick_query: Query<PickQuery, With<EditorEntity>>,
+) {
+    if !mouse_button.just_released(MouseButton::Left) {
+        return;
+    }
+    let Some(start) = box_select.start.take() else {
+        return;
+    };
+
+    if let Some(end) = world_mouse.position {
+        if !box_select.additive {
+            selection.clear();
+        }
+        selection.extend(entities_in_box(start, end, &pick_query));
+    }
+}
+
 fn drag_entity(
     placement: Res<PlacementState>,
     mouse_button: Res<ButtonInput<MouseButton>>,
     world_mouse: Res<WorldMousePosition>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    editor_prefs: Res<EditorPrefs>,
+    camera: Single<&Transform, (With<WorldCamera>, Without<EditorEntity>)>,
     selection: Res<SelectionState>,
     mut drag_state: ResMut<DragState>,
-    mut query: Query<&mut Transform, With<EditorEntity>>,
+    mut guides: ResMut<SnapGuides>,
+    mut history: ResMut<EditHistory>,
+    mut queries: ParamSet<(
+        Query<&mut Transform, With<EditorEntity>>,
+        Query<SceneEntityQuery, With<EditorEntity>>,
+        Query<PickQuery, With<EditorEntity>>,
+    )>,
     game_view_tab: Res<GameViewTab>,
 ) {
     if placement.tool != Tool::Move {
         return;
     }
+    guides.lines.clear();
 
     if !game_view_tab.mouse_in {
         return;
     }
 
     if mouse_button.just_pressed(MouseButton::Left) {
-        if let Some(entity) = selection.selected_entity {
-            if let Ok(transform) = query.get(entity) {
-                if let Some(pos) = world_mouse.position {
-                    let entity_pos = transform.translation.truncate();
-                    drag_state.dragging = true;
-                    drag_state.entity = Some(entity);
-                    drag_state.offset = entity_pos - pos;
-                }
-            }
+        if let Some(pos) = world_mouse.position {
+            let query = queries.p1();
+            drag_state.entities = selection
+                .entities
+                .iter()
+                .filter_map(|entity| query.get(*entity).ok())
+                .map(|item| DraggedEntity {
+                    entity: item.entity,
+                    offset: item.transform.translation.truncate() - pos,
+                    before: item.to_data(),
+                })
+                .collect();
+            drag_state.dragging = !drag_state.entities.is_empty();
         }
     }
 
     if mouse_button.pressed(MouseButton::Left) && drag_state.dragging {
-        if let Some(entity) = drag_state.entity {
-            if let Some(pos) = world_mouse.position {
-                if let Ok(mut transform) = query.get_mut(entity) {
-                    let new_pos = pos + drag_state.offset;
+        if let Some(pos) = world_mouse.position {
+            // The group follows the primary entity's snapped position.
+            let mut snap = Vec2::ZERO;
+            let lead = drag_state
+                .entities
+                .iter()
+                .find(|dragged| selection.selected_entity == Some(dragged.entity))
+                .or(drag_state.entities.first());
+            if let Some(lead) = lead
+                && !keyboard.any_pressed(SNAP_OVERRIDE_KEYS)
+            {
+                let query = queries.p2();
+                if let Ok(item) = query.get(lead.entity) {
+                    let bounds = item.bounds();
+                    let translation = item.transform.translation.truncate();
+                    let targets: Vec<_> = query
+                        .iter()
+                        .filter(|other| {
+                            !drag_state
+                                .entities
+                                .iter()
+                                .any(|dragged| dragged.entity == other.entity)
+                        })
+                        .map(|other| other.bounds())
+                        .collect();
+                    let target = pos + lead.offset;
+                    snap = snap_position(
+                        target,
+                        Rect {
+                            min: bounds.min - translation,
+                            max: bounds.max - translation,
+                        },
+                        &targets,
+                        &editor_prefs,
+                        GUIDE_TOLERANCE * camera.scale.x,
+                        &mut guides.lines,
+                    ) - target;
+                }
+            }
+
+            let mut query = queries.p0();
+            for dragged in &drag_state.entities {
+                if let Ok(mut transform) = query.get_mut(dragged.entity) {
+                    let new_pos = pos + dragged.offset + snap;
                     transform.translation.x = new_pos.x;
                     transform.translation.y = new_pos.y;
                 }
@@ -239,8 +354,25 @@ fn drag_entity(
     }
 
     if mouse_button.just_released(MouseButton::Left) {
+        let query = queries.p1();
+        let moved: Vec<_> = drag_state
+            .entities
+            .drain(..)
+            .filter_map(|dragged| {
+                let item = query.get(dragged.entity).ok()?;
+                (item.transform.translation != dragged.before.transform.translation).then(|| {
+                    EditCommand::Modify {
+                        entity: dragged.entity,
+                        before: dragged.before,
+                        after: item.to_data(),
+                    }
+                })
+            })
+            .collect();
+        if !moved.is_empty() {
+            history.record("Move", EditCommand::group(moved));
+        }
         drag_state.dragging = false;
-        drag_state.entity = None;
     }
 }
 
@@ -264,7 +396,9 @@ fn update_selected_entity_visual(
         commands.entity(entity).remove::<SelectedEntity>();
     }
 
-    if let Some(entity) = selection.selected_entity {
-        commands.entity(entity).insert(SelectedEntity);
+    for &entity in &selection.entities {
+        if let Ok(mut entity_commands) = commands.get_entity(entity) {
+            entity_commands.insert(SelectedEntity);
+        }
     }
 }
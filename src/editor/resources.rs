@@ -1,4 +1,7 @@
 use crate::{
+    project::file_format::{
+        ColliderData, FieldData, FrictionData, RigidBodyData, SceneEntityData, TransformData,
+    },
     shared::EntityShape,
     simulation::{Charge, Field},
 };
@@ -13,9 +16,11 @@ pub enum Tool {
     Move,
     Place,
     Delete,
+    Rotate,
+    Scale,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum RigidBodyType {
     #[default]
     Dynamic,
@@ -23,7 +28,27 @@ pub enum RigidBodyType {
     Kinematic,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+impl From<RigidBody> for RigidBodyType {
+    fn from(rigid_body: RigidBody) -> Self {
+        match rigid_body {
+            RigidBody::Dynamic => RigidBodyType::Dynamic,
+            RigidBody::Static => RigidBodyType::Static,
+            RigidBody::Kinematic => RigidBodyType::Kinematic,
+        }
+    }
+}
+
+impl From<RigidBodyType> for RigidBody {
+    fn from(body_type: RigidBodyType) -> Self {
+        match body_type {
+            RigidBodyType::Dynamic => RigidBody::Dynamic,
+            RigidBodyType::Static => RigidBody::Static,
+            RigidBodyType::Kinematic => RigidBody::Kinematic,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, serde::Serialize, serde::Deserialize)]
 pub enum FieldType {
     #[default]
     None,
@@ -45,6 +70,7 @@ pub struct PlacementState {
     pub friction: Friction,
     pub initial_velocity: LinearVelocity,
     pub constant_force: ConstantForce,
+    pub color: Color,
 }
 
 impl Default for PlacementState {
@@ -62,6 +88,35 @@ impl Default for PlacementState {
             friction: Friction::ZERO,
             initial_velocity: LinearVelocity::ZERO,
             constant_force: ConstantForce::new(0.0, 0.0),
+            color: Color::WHITE,
+        }
+    }
+}
+
+impl PlacementState {
+    /// Description of the entity the Place tool would create at `transform`.
+    pub fn entity_data(&self, name: String, transform: Transform) -> SceneEntityData {
+        let is_field = self.field_type != FieldType::None;
+
+        SceneEntityData {
+            name,
+            transform: TransformData::from_transform(&transform),
+            color: self.color,
+            rigid_body: Some(RigidBodyData {
+                body_type: self.rigid_body.into(),
+                // [IMPORTANT] 场不能有Mass
+                mass: (!is_field).then_some(self.mass),
+                friction: FrictionData::from_friction(&self.friction),
+                initial_velocity: self.initial_velocity.0,
+                angular_velocity: 0.0,
+                constant_force: self.constant_force.0,
+            }),
+            collider: Some(ColliderData {
+                shape: self.shape,
+                size: self.size,
+            }),
+            charge: (!is_field).then_some(self.charge.value),
+            field: is_field.then(|| FieldData::from_field(self.field_type, &self.field)),
         }
     }
 }
@@ -80,6 +135,12 @@ pub struct IndicatorInfo {
     pub pointer_over_ui: bool,
 }
 
+/// Alignment lines shown while placing or dragging with snapping.
+#[derive(Resource, Default)]
+pub struct SnapGuides {
+    pub lines: Vec<(Vec2, Vec2)>,
+}
+
 #[derive(Resource, Default)]
 pub struct EntityProperties {
     pub entity: Option<Entity>,
@@ -94,14 +155,100 @@ pub struct EntityProperties {
 #[derive(Resource, Default)]
 pub struct ClearRequest(pub bool);
 
+/// The selected entities. `selected_entity` is the primary one, shown in the
+/// inspector, and is always part of `entities`.
 #[derive(Resource, Default)]
 pub struct SelectionState {
     pub selected_entity: Option<Entity>,
+    pub entities: Vec<Entity>,
+}
+
+impl SelectionState {
+    /// Replaces the selection with `entity`.
+    pub fn select(&mut self, entity: Entity) {
+        self.entities.clear();
+        self.entities.push(entity);
+        self.selected_entity = Some(entity);
+    }
+
+    /// Adds `entity` to the selection, or removes it if already selected.
+    pub fn toggle(&mut self, entity: Entity) {
+        if self.contains(entity) {
+            self.remove(entity);
+        } else {
+            self.entities.push(entity);
+            self.selected_entity = Some(entity);
+        }
+    }
+
+    pub fn extend(&mut self, entities: impl IntoIterator<Item = Entity>) {
+        for entity in entities {
+            if !self.contains(entity) {
+                self.entities.push(entity);
+            }
+        }
+        if self.selected_entity.is_none() {
+            self.selected_entity = self.entities.last().copied();
+        }
+    }
+
+    pub fn remove(&mut self, entity: Entity) {
+        self.entities.retain(|e| *e != entity);
+        if self.selected_entity == Some(entity) {
+            self.selected_entity = self.entities.last().copied();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entities.clear();
+        self.selected_entity = None;
+    }
+
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.entities.contains(&entity)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+/// An in-progress rubber-band selection in the viewport.
+#[derive(Resource, Default)]
+pub struct BoxSelectState {
+    /// World position where the drag started.
+    pub start: Option<Vec2>,
+    /// Whether the boxed entities are added to the existing selection.
+    pub additive: bool,
 }
 
 #[derive(Resource, Default)]
 pub struct DragState {
     pub dragging: bool,
-    pub entity: Option<Entity>,
+    pub entities: Vec<DraggedEntity>,
+}
+
+pub struct DraggedEntity {
+    pub entity: Entity,
     pub offset: Vec2,
+    /// The entity as it was when the drag started, for the history.
+    pub before: SceneEntityData,
+}
+
+/// Part of the rotate/scale gizmo being dragged.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GizmoHandle {
+    Ring,
+    /// An edge of the collider, as its outward direction in the entity's frame.
+    Edge(Vec2),
+}
+
+#[derive(Resource, Default)]
+pub struct GizmoState {
+    pub entity: Option<Entity>,
+    pub handle: Option<GizmoHandle>,
+    /// Pointer position when the drag started.
+    pub start: Vec2,
+    /// The entity as it was when the drag started, for the history.
+    pub before: Option<SceneEntityData>,
 }
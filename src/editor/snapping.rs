@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+use crate::settings::editor_prefs::EditorPrefs;
+
+/// Screen distance, in pixels at a camera scale of 1, within which an edge or
+/// centre aligns to another entity's.
+pub const GUIDE_TOLERANCE: f32 = 6.0;
+
+/// Holding either key places and drags at the raw mouse position.
+pub const SNAP_OVERRIDE_KEYS: [KeyCode; 2] = [KeyCode::AltLeft, KeyCode::AltRight];
+
+/// Aligns `position` to other entities and the grid.
+///
+/// `bounds` is the moving shape relative to `position`, and `targets` the
+/// bounds of the entities it can align to. On each axis an alignment of
+/// centres or edges within `tolerance` wins over the grid; every such
+/// alignment adds a guide line to `guides`.
+pub fn snap_position(
+    position: Vec2,
+    bounds: Rect,
+    targets: &[Rect],
+    prefs: &EditorPrefs,
+    tolerance: f32,
+    guides: &mut Vec<(Vec2, Vec2)>,
+) -> Vec2 {
+    let moving = Rect {
+        min: bounds.min + position,
+        max: bounds.max + position,
+    };
+    let mut snapped = position;
+
+    for axis in 0..2 {
+        let features = [moving.min[axis], moving.center()[axis], moving.max[axis]];
+        let mut best: Option<(f32, &Rect, f32)> = None;
+        for target in targets {
+            for line in [target.min[axis], target.center()[axis], target.max[axis]] {
+                for feature in features {
+                    let delta = line - feature;
+                    if delta.abs() < tolerance
+                        && best.is_none_or(|(best_delta, ..)| delta.abs() < best_delta.abs())
+                    {
+                        best = Some((delta, target, line));
+                    }
+                }
+            }
+        }
+
+        if let Some((delta, target, line)) = best {
+            snapped[axis] += delta;
+            let other = 1 - axis;
+            let mut start = Vec2::ZERO;
+            let mut end = Vec2::ZERO;
+            start[axis] = line;
+            end[axis] = line;
+            start[other] = moving.min[other].min(target.min[other]);
+            end[other] = moving.max[other].max(target.max[other]);
+            guides.push((start, end));
+        } else if prefs.snap_to_grid && prefs.grid_size > 0.0 {
+            snapped[axis] = (position[axis] / prefs.grid_size).round() * prefs.grid_size;
+        }
+    }
+
+    snapped
+}
@@ -5,6 +5,7 @@ use bevy_enhanced_input::prelude::*;
 
 use crate::app::SimulationState;
 use crate::camera::components::WorldCamera;
+use crate::editor::EditHistory;
 use crate::editor::PlacementState;
 use crate::editor::Tool;
 use crate::ui::GameViewTab;
@@ -18,6 +19,8 @@ impl Plugin for ActionPlugin {
             .add_input_context::<BoardContext>()
             .add_input_context::<MouseContext>()
             .add_observer(handle_toggle_simulation)
+            .add_observer(handle_undo)
+            .add_observer(handle_redo)
             .add_observer(handle_camera_pan)
             .add_observer(handle_camera_zoom)
             .add_systems(Startup, setup_context);
@@ -35,6 +38,18 @@ fn setup_context(mut commands: Commands) {
         BoardContext,
         actions!(BoardContext[
             (Action::<ToggleSimulation>::new(), bindings![KeyCode::Space]),
+            // Redo comes first so that it consumes Ctrl+Shift+Z before undo sees it.
+            (
+                Action::<Redo>::new(),
+                bindings![
+                    KeyCode::KeyZ.with_mod_keys(ModKeys::CONTROL | ModKeys::SHIFT),
+                    KeyCode::KeyY.with_mod_keys(ModKeys::CONTROL),
+                ],
+            ),
+            (
+                Action::<Undo>::new(),
+                bindings![KeyCode::KeyZ.with_mod_keys(ModKeys::CONTROL)],
+            ),
         ]),
     ));
     commands.spawn((
@@ -64,6 +79,14 @@ fn setup_context(mut commands: Commands) {
 #[action_output(bool)]
 pub struct ToggleSimulation;
 
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct Undo;
+
+#[derive(InputAction)]
+#[action_output(bool)]
+pub struct Redo;
+
 #[derive(InputAction)]
 #[action_output(bool)]
 struct MouseLeft;
@@ -83,6 +106,14 @@ pub fn handle_toggle_simulation(
     state.is_running = !state.is_running;
 }
 
+pub fn handle_undo(_action: On<Start<Undo>>, mut history: ResMut<EditHistory>) {
+    history.undo();
+}
+
+pub fn handle_redo(_action: On<Start<Redo>>, mut history: ResMut<EditHistory>) {
+    history.redo();
+}
+
 pub fn handle_camera_pan(
     action: On<Fire<CameraPan>>,
     mut camera: Single<&mut Transform, With<WorldCamera>>,
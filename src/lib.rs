@@ -0,0 +1,12 @@
+pub mod app;
+pub mod batch;
+pub mod camera;
+#[cfg(feature = "dev")]
+pub mod dev;
+pub mod editor;
+pub mod input;
+pub mod project;
+pub mod settings;
+pub mod shared;
+pub mod simulation;
+pub mod ui;
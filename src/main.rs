@@ -1,16 +1,5 @@
-pub mod app;
-pub mod camera;
-#[cfg(feature = "dev")]
-pub mod dev;
-pub mod editor;
-pub mod input;
-pub mod project;
-pub mod settings;
-pub mod shared;
-pub mod simulation;
-pub mod ui;
-
 use bevy::prelude::*;
+use simphy::{app, camera, editor, input, project, simulation, ui};
 
 fn main() {
     let mut app = App::new();
@@ -18,11 +7,12 @@ fn main() {
         .add_plugins(simulation::SimulationPlugin)
         .add_plugins(input::ActionPlugin)
         .add_plugins(editor::EditorPlugin)
+        .add_plugins(project::ProjectPlugin)
         .add_plugins(ui::UiPlugin)
         .add_plugins(camera::CameraPlugin);
 
     #[cfg(feature = "dev")]
-    app.add_plugins(dev::DevSetupPlugin);
+    app.add_plugins(simphy::dev::DevSetupPlugin);
 
     app.run();
 }
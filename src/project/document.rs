@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use super::file_format::SceneData;
+use super::file_format::{EnvironmentData, SceneData};
 use bevy::prelude::*;
 
 #[derive(Resource, Default)]
@@ -8,6 +8,11 @@ pub struct Document {
     pub path: Option<PathBuf>,
     pub is_dirty: bool,
     pub data: SceneData,
+    /// Set when `data` was replaced and the world should be rebuilt from it.
+    pub needs_rebuild: bool,
+    /// Environment as last saved or opened; differing settings make the
+    /// document dirty, as environment edits aren't in the edit history.
+    pub saved_environment: EnvironmentData,
 }
 
 impl Document {
@@ -16,6 +21,8 @@ impl Document {
             path: None,
             is_dirty: false,
             data: SceneData::default(),
+            needs_rebuild: false,
+            saved_environment: EnvironmentData::default(),
         }
     }
 }
@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// Why a scene could not be read or written.
+#[derive(Debug)]
+pub enum ProjectError {
+    Io(std::io::Error),
+    UnsupportedFormat(Option<String>),
+    Parse {
+        format: &'static str,
+        message: String,
+    },
+    Serialize {
+        format: &'static str,
+        message: String,
+    },
+    /// The file was written by a newer build than this one.
+    NewerVersion {
+        found: u32,
+        supported: u32,
+    },
+    /// A value in the file names something this build doesn't know.
+    InvalidValue {
+        field: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for ProjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProjectError::Io(e) => write!(f, "Failed to access file: {}", e),
+            ProjectError::UnsupportedFormat(Some(extension)) => {
+                write!(f, "Unsupported file format: .{}", extension)
+            }
+            ProjectError::UnsupportedFormat(None) => write!(f, "Unsupported file format"),
+            ProjectError::Parse { format, message } => {
+                write!(f, "Failed to parse {}: {}", format, message)
+            }
+            ProjectError::Serialize { format, message } => {
+                write!(f, "Failed to serialize {}: {}", format, message)
+            }
+            ProjectError::NewerVersion { found, supported } => write!(
+                f,
+                "Scene format version {} is newer than supported version {}",
+                found, supported
+            ),
+            ProjectError::InvalidValue { field, value } => {
+                write!(f, "Invalid {}: \"{}\"", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProjectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProjectError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for ProjectError {
+    fn from(e: std::io::Error) -> Self {
+        ProjectError::Io(e)
+    }
+}
@@ -1,47 +1,182 @@
+use avian2d::prelude::Friction;
 use bevy::prelude::*;
 
+use crate::editor::{FieldType, RigidBodyType};
+use crate::shared::EntityShape;
+use crate::simulation::{
+    ChargeIntegrator, ConservationSettings, CoulombSettings, Field, FieldModulation, FieldProfile,
+    GravitySettings, PhysicsSettings, SimulationSettings, SolverSettings,
+};
+
 #[derive(Component)]
 pub struct SceneEntity {
     pub name: String,
 }
 
-#[derive(serde::Serialize, serde::Deserialize, Default)]
+/// Version written by this build. Bump it and add a step to `migration` whenever
+/// a change to these types would stop older files from parsing.
+pub const FORMAT_VERSION: u32 = 1;
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
 pub struct SceneData {
+    /// Missing in files written before versioning, which are version 0.
+    #[serde(default)]
+    pub format_version: u32,
+    #[serde(default)]
+    pub environment: EnvironmentData,
     pub entities: Vec<SceneEntityData>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+impl Default for SceneData {
+    fn default() -> Self {
+        Self {
+            format_version: FORMAT_VERSION,
+            environment: EnvironmentData::default(),
+            entities: Vec::new(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default)]
+pub struct EnvironmentData {
+    pub gravity: GravitySettings,
+    pub coulomb: CoulombSettings,
+    pub solver: SolverSettings,
+    pub integrator: ChargeIntegrator,
+    pub physics: PhysicsSettings,
+    pub conservation: ConservationSettings,
+}
+
+impl EnvironmentData {
+    pub fn from_settings(settings: &SimulationSettings) -> Self {
+        Self {
+            gravity: *settings.gravity,
+            coulomb: *settings.coulomb,
+            solver: *settings.solver,
+            integrator: *settings.integrator,
+            physics: *settings.physics,
+            conservation: *settings.conservation,
+        }
+    }
+
+    pub fn apply(&self, settings: &mut SimulationSettings) {
+        *settings.gravity = self.gravity;
+        *settings.coulomb = self.coulomb;
+        *settings.solver = self.solver;
+        *settings.integrator = self.integrator;
+        *settings.physics = self.physics;
+        *settings.conservation = self.conservation;
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
 pub struct SceneEntityData {
     pub name: String,
     pub transform: TransformData,
+    #[serde(default = "default_color")]
+    pub color: Color,
     pub rigid_body: Option<RigidBodyData>,
     pub collider: Option<ColliderData>,
     pub charge: Option<f32>,
     pub field: Option<FieldData>,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+fn default_color() -> Color {
+    Color::WHITE
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct TransformData {
     pub translation: Vec3,
+    /// Rotation about the Z axis, in radians.
     pub rotation: f32,
     pub scale: Vec3,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+impl TransformData {
+    pub fn from_transform(transform: &Transform) -> Self {
+        Self {
+            translation: transform.translation,
+            rotation: transform.rotation.to_euler(EulerRot::ZYX).0,
+            scale: transform.scale,
+        }
+    }
+
+    pub fn to_transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: Quat::from_rotation_z(self.rotation),
+            scale: self.scale,
+        }
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct RigidBodyData {
-    pub body_type: String,
+    pub body_type: RigidBodyType,
+    /// Fields never carry a mass.
+    pub mass: Option<f32>,
+    pub friction: FrictionData,
+    pub initial_velocity: Vec2,
+    #[serde(default)]
+    pub angular_velocity: f32,
+    pub constant_force: Vec2,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct FrictionData {
+    pub dynamic_coefficient: f32,
+    pub static_coefficient: f32,
+}
+
+impl FrictionData {
+    pub fn from_friction(friction: &Friction) -> Self {
+        Self {
+            dynamic_coefficient: friction.dynamic_coefficient,
+            static_coefficient: friction.static_coefficient,
+        }
+    }
+
+    pub fn to_friction(&self) -> Friction {
+        Friction::new(self.dynamic_coefficient).with_static_coefficient(self.static_coefficient)
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct ColliderData {
-    pub shape: String,
-    pub half_extents: Option<Vec2>,
-    pub radius: Option<f32>,
+    pub shape: EntityShape,
+    pub size: Vec2,
 }
 
-#[derive(serde::Serialize, serde::Deserialize)]
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, PartialEq)]
 pub struct FieldData {
-    pub field_type: String,
+    pub field_type: FieldType,
     pub strength: f32,
-    pub direction: Vec2,
+    pub direction: Vec3,
+    #[serde(default)]
+    pub profile: FieldProfile,
+    #[serde(default)]
+    pub modulation: FieldModulation,
+}
+
+impl FieldData {
+    pub fn from_field(field_type: FieldType, field: &Field) -> Self {
+        Self {
+            field_type,
+            strength: field.strength,
+            direction: field.direction,
+            profile: field.profile.clone(),
+            modulation: field.modulation.clone(),
+        }
+    }
+
+    pub fn to_field(&self) -> Field {
+        Field {
+            strength: self.strength,
+            direction: self.direction,
+            profile: self.profile.clone(),
+            modulation: self.modulation.clone(),
+        }
+    }
 }
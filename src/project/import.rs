@@ -1,30 +1,59 @@
-use super::file_format::SceneData;
+use serde::de::DeserializeOwned;
 use std::path::Path;
 
-pub fn import_yaml(path: &Path) -> Result<SceneData, String> {
-    let content =
-        std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+use super::error::ProjectError;
+use super::file_format::SceneData;
+use super::migration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneFormat {
+    Yaml,
+    Ron,
+}
 
-    let data: SceneData =
-        serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse YAML: {}", e))?;
+impl SceneFormat {
+    pub fn from_path(path: &Path) -> Result<Self, ProjectError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("yaml") | Some("yml") => Ok(SceneFormat::Yaml),
+            Some("ron") => Ok(SceneFormat::Ron),
+            extension => Err(ProjectError::UnsupportedFormat(
+                extension.map(str::to_string),
+            )),
+        }
+    }
 
-    Ok(data)
+    pub fn name(&self) -> &'static str {
+        match self {
+            SceneFormat::Yaml => "YAML",
+            SceneFormat::Ron => "RON",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self, content: &str) -> Result<T, ProjectError> {
+        let result = match self {
+            SceneFormat::Yaml => serde_yaml::from_str(content).map_err(|e| e.to_string()),
+            SceneFormat::Ron => ron::from_str(content).map_err(|e| e.to_string()),
+        };
+        result.map_err(|message| ProjectError::Parse {
+            format: self.name(),
+            message,
+        })
+    }
 }
 
-pub fn import_ron(path: &Path) -> Result<SceneData, String> {
-    let content =
-        std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+fn import_as(path: &Path, format: SceneFormat) -> Result<SceneData, ProjectError> {
+    let content = std::fs::read_to_string(path)?;
+    migration::upgrade(&content, format)
+}
 
-    let data: SceneData =
-        ron::from_str(&content).map_err(|e| format!("Failed to parse RON: {}", e))?;
+pub fn import_yaml(path: &Path) -> Result<SceneData, ProjectError> {
+    import_as(path, SceneFormat::Yaml)
+}
 
-    Ok(data)
+pub fn import_ron(path: &Path) -> Result<SceneData, ProjectError> {
+    import_as(path, SceneFormat::Ron)
 }
 
-pub fn import_scene(path: &Path) -> Result<SceneData, String> {
-    match path.extension().and_then(|e| e.to_str()) {
-        Some("yaml") | Some("yml") => import_yaml(path),
-        Some("ron") => import_ron(path),
-        _ => Err("Unsupported file format".to_string()),
-    }
+pub fn import_scene(path: &Path) -> Result<SceneData, ProjectError> {
+    import_as(path, SceneFormat::from_path(path)?)
 }
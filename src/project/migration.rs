@@ -0,0 +1,182 @@
+//! Upgrades scene files written by older builds to the current `SceneData`.
+//!
+//! Each old schema lives in its own module and converts into the next one with
+//! `From`, so a file is upgraded one version at a time until it is current.
+
+use bevy::prelude::*;
+
+use super::error::ProjectError;
+use super::file_format::{
+    ColliderData, FORMAT_VERSION, FieldData, FrictionData, RigidBodyData, SceneData,
+    SceneEntityData,
+};
+use super::import::SceneFormat;
+use crate::editor::{FieldType, RigidBodyType};
+use crate::shared::EntityShape;
+
+#[derive(serde::Deserialize)]
+struct VersionHeader {
+    #[serde(default)]
+    format_version: u32,
+}
+
+pub fn upgrade(content: &str, format: SceneFormat) -> Result<SceneData, ProjectError> {
+    let header: VersionHeader = format.parse(content)?;
+    match header.format_version {
+        0 => format.parse::<v0::SceneData>(content)?.try_into(),
+        FORMAT_VERSION => format.parse(content),
+        found => Err(ProjectError::NewerVersion {
+            found,
+            supported: FORMAT_VERSION,
+        }),
+    }
+}
+
+/// Unversioned files. Bodies only recorded their type, colliders were never
+/// written and enum values were free-form strings.
+mod v0 {
+    use bevy::prelude::*;
+
+    use crate::project::file_format::{EnvironmentData, TransformData};
+    use crate::simulation::{FieldModulation, FieldProfile};
+
+    #[derive(serde::Deserialize)]
+    pub struct SceneData {
+        #[serde(default)]
+        pub environment: EnvironmentData,
+        pub entities: Vec<SceneEntityData>,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct SceneEntityData {
+        pub name: String,
+        pub transform: TransformData,
+        pub rigid_body: Option<RigidBodyData>,
+        pub collider: Option<ColliderData>,
+        pub charge: Option<f32>,
+        pub field: Option<FieldData>,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct RigidBodyData {
+        pub body_type: String,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct ColliderData {
+        pub shape: String,
+        pub half_extents: Option<Vec2>,
+        pub radius: Option<f32>,
+    }
+
+    #[derive(serde::Deserialize)]
+    pub struct FieldData {
+        pub field_type: String,
+        pub strength: f32,
+        pub direction: Vec2,
+        #[serde(default)]
+        pub profile: FieldProfile,
+        #[serde(default)]
+        pub modulation: FieldModulation,
+    }
+}
+
+impl TryFrom<v0::SceneData> for SceneData {
+    type Error = ProjectError;
+
+    fn try_from(old: v0::SceneData) -> Result<Self, ProjectError> {
+        Ok(Self {
+            format_version: FORMAT_VERSION,
+            environment: old.environment,
+            entities: old
+                .entities
+                .into_iter()
+                .map(TryInto::try_into)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl TryFrom<v0::SceneEntityData> for SceneEntityData {
+    type Error = ProjectError;
+
+    fn try_from(old: v0::SceneEntityData) -> Result<Self, ProjectError> {
+        let is_field = old.field.is_some();
+
+        let rigid_body = old.rigid_body.map(|rigid_body| RigidBodyData {
+            body_type: match rigid_body.body_type.as_str() {
+                "Static" => RigidBodyType::Static,
+                "Kinematic" => RigidBodyType::Kinematic,
+                _ => RigidBodyType::Dynamic,
+            },
+            // The placement default, fields never carry a mass.
+            mass: (!is_field).then_some(1.0),
+            friction: FrictionData::default(),
+            initial_velocity: Vec2::ZERO,
+            angular_velocity: 0.0,
+            constant_force: Vec2::ZERO,
+        });
+
+        let collider = match old.collider {
+            Some(collider) => ColliderData {
+                shape: match collider.shape.as_str() {
+                    "Circle" => EntityShape::Circle,
+                    "Triangle" => EntityShape::Triangle,
+                    "Pentagon" => EntityShape::Pentagon,
+                    "Hexagon" => EntityShape::Hexagon,
+                    "Star" => EntityShape::Star,
+                    "Diamond" => EntityShape::Diamond,
+                    "Cross" => EntityShape::Cross,
+                    _ => EntityShape::Rectangle,
+                },
+                size: collider
+                    .half_extents
+                    .map(|half_extents| half_extents * 2.0)
+                    .or(collider.radius.map(|radius| Vec2::splat(radius * 2.0)))
+                    .unwrap_or(Vec2::splat(50.0)),
+            },
+            None => ColliderData {
+                shape: EntityShape::default(),
+                size: Vec2::splat(50.0),
+            },
+        };
+
+        let field = old
+            .field
+            .map(|field| {
+                let field_type = match field.field_type.as_str() {
+                    "magnetic" => FieldType::Magnetic,
+                    "electric" => FieldType::Electric,
+                    _ => {
+                        return Err(ProjectError::InvalidValue {
+                            field: "field type",
+                            value: field.field_type,
+                        });
+                    }
+                };
+                Ok(FieldData {
+                    field_type,
+                    strength: field.strength,
+                    // Only x and y were written, which dropped the default out-of-plane direction.
+                    direction: if field.direction == Vec2::ZERO {
+                        Vec3::Z
+                    } else {
+                        field.direction.extend(0.0)
+                    },
+                    profile: field.profile,
+                    modulation: field.modulation,
+                })
+            })
+            .transpose()?;
+
+        Ok(Self {
+            name: old.name,
+            transform: old.transform,
+            color: Color::WHITE,
+            rigid_body,
+            collider: Some(collider),
+            charge: old.charge,
+            field,
+        })
+    }
+}
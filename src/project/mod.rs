@@ -1,9 +1,24 @@
 pub mod document;
+pub mod error;
 pub mod export;
 pub mod file_format;
 pub mod import;
+pub mod migration;
+pub mod plugin;
+pub mod scene;
+pub mod trajectory;
 
 pub use document::Document;
+pub use error::ProjectError;
 pub use export::*;
 pub use file_format::*;
 pub use import::*;
+pub use plugin::ProjectPlugin;
+pub use scene::{
+    SceneEntityQuery, SceneEntityQueryItem, collect_scene_data, insert_scene_entity,
+    instantiate_scene, spawn_scene_entity,
+};
+pub use trajectory::{
+    Trajectory, TrajectoryData, TrajectoryFormat, TrajectorySample, export_trajectories,
+    write_trajectories,
+};
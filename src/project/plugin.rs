@@ -0,0 +1,13 @@
+use bevy::prelude::*;
+
+use super::document::Document;
+use super::scene::instantiate_scene;
+
+pub struct ProjectPlugin;
+
+impl Plugin for ProjectPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Document::new())
+            .add_systems(PreUpdate, instantiate_scene);
+    }
+}
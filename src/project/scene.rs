@@ -0,0 +1,195 @@
+use avian2d::prelude::*;
+use bevy::ecs::query::{QueryData, QueryFilter};
+use bevy::prelude::*;
+
+use super::document::Document;
+use super::file_format::{
+    ColliderData, EnvironmentData, FORMAT_VERSION, FieldData, FrictionData, RigidBodyData,
+    SceneData, SceneEntityData, TransformData,
+};
+use crate::app::{SimulationControl, SimulationSnapshot};
+use crate::editor::{
+    EditHistory, EditorEntity, EntityName, FieldType, PlacedEntities, PlacedEntity, SelectionState,
+};
+use crate::shared::EntityShape;
+use crate::simulation::{Charge, Electric, Field, Magnetic, SimulationClock, SimulationSettings};
+
+/// Everything needed to write an editor entity back out as `SceneEntityData`.
+#[derive(QueryData)]
+pub struct SceneEntityQuery {
+    pub entity: Entity,
+    pub transform: &'static Transform,
+    pub name: Option<&'static EntityName>,
+    pub shape: Option<&'static EntityShape>,
+    pub placed: Option<&'static PlacedEntity>,
+    pub rigid_body: Option<&'static RigidBody>,
+    pub mass: Option<&'static Mass>,
+    pub friction: Option<&'static Friction>,
+    pub linear_velocity: Option<&'static LinearVelocity>,
+    pub angular_velocity: Option<&'static AngularVelocity>,
+    pub constant_force: Option<&'static ConstantForce>,
+    pub charge: Option<&'static Charge>,
+    pub field: Option<&'static Field>,
+    pub magnetic: Has<Magnetic>,
+    pub electric: Has<Electric>,
+}
+
+impl SceneEntityQueryItem<'_, '_> {
+    pub fn to_data(&self) -> SceneEntityData {
+        let placed = self.placed.copied().unwrap_or(PlacedEntity {
+            size: Vec2::splat(50.0),
+            color: Color::WHITE,
+        });
+
+        let rigid_body = self.rigid_body.map(|rigid_body| RigidBodyData {
+            body_type: (*rigid_body).into(),
+            mass: self.mass.map(|m| m.0),
+            friction: self
+                .friction
+                .map(FrictionData::from_friction)
+                .unwrap_or_default(),
+            initial_velocity: self.linear_velocity.map(|v| v.0).unwrap_or_default(),
+            angular_velocity: self.angular_velocity.map(|v| v.0).unwrap_or_default(),
+            constant_force: self.constant_force.map(|f| f.0).unwrap_or_default(),
+        });
+
+        let collider = self.shape.map(|shape| ColliderData {
+            shape: *shape,
+            size: placed.size,
+        });
+
+        let field_type = if self.magnetic {
+            FieldType::Magnetic
+        } else if self.electric {
+            FieldType::Electric
+        } else {
+            FieldType::None
+        };
+        let field = self
+            .field
+            .map(|field| FieldData::from_field(field_type, field));
+
+        SceneEntityData {
+            name: self.name.map(|n| n.0.clone()).unwrap_or_default(),
+            transform: TransformData::from_transform(self.transform),
+            color: placed.color,
+            rigid_body,
+            collider,
+            charge: self.charge.map(|c| c.value),
+            field,
+        }
+    }
+}
+
+/// Entities are written in spawn order so that reloading a scene keeps it.
+pub fn collect_scene_data<F: QueryFilter>(
+    query: &Query<SceneEntityQuery, F>,
+    settings: &SimulationSettings,
+) -> SceneData {
+    let mut items: Vec<_> = query.iter().collect();
+    items.sort_by_key(|item| item.entity);
+
+    SceneData {
+        format_version: FORMAT_VERSION,
+        environment: EnvironmentData::from_settings(settings),
+        entities: items.iter().map(|item| item.to_data()).collect(),
+    }
+}
+
+/// Spawns an editor entity with the same components `place_entity` would give it.
+pub fn spawn_scene_entity(commands: &mut Commands, data: &SceneEntityData) -> Entity {
+    let mut entity_commands = commands.spawn(EditorEntity);
+    insert_scene_entity(&mut entity_commands, data);
+    entity_commands.id()
+}
+
+/// Inserts the components described by `data`, overwriting existing ones.
+pub fn insert_scene_entity(entity_commands: &mut EntityCommands, data: &SceneEntityData) {
+    let size = data
+        .collider
+        .map(|collider| collider.size)
+        .unwrap_or(Vec2::splat(50.0));
+
+    entity_commands.insert((
+        EntityName(data.name.clone()),
+        data.transform.to_transform(),
+        PlacedEntity {
+            size,
+            color: data.color,
+        },
+        DebugRender::default().with_collider_color(data.color),
+    ));
+
+    if let Some(collider) = data.collider {
+        entity_commands.insert((collider.shape, collider.shape.to_collider(collider.size)));
+    }
+
+    if let Some(rigid_body) = data.rigid_body {
+        entity_commands.insert((
+            RigidBody::from(rigid_body.body_type),
+            LinearVelocity(rigid_body.initial_velocity),
+            AngularVelocity(rigid_body.angular_velocity),
+            rigid_body.friction.to_friction(),
+            ConstantForce(rigid_body.constant_force),
+        ));
+        if let Some(mass) = rigid_body.mass {
+            entity_commands.insert(Mass(mass));
+        }
+    }
+
+    if let Some(charge) = data.charge {
+        entity_commands.insert(Charge::new(charge));
+    }
+
+    if let Some(field) = &data.field {
+        match field.field_type {
+            FieldType::Magnetic => {
+                entity_commands.insert(Magnetic);
+            }
+            FieldType::Electric => {
+                entity_commands.insert(Electric);
+            }
+            FieldType::None => {}
+        }
+        entity_commands.insert((
+            Sensor,
+            field.to_field(),
+            CollisionEventsEnabled,
+            CollidingEntities::default(),
+        ));
+    }
+}
+
+/// Replaces the editor entities with `Document::data` after a scene was opened
+/// or a new one started.
+pub fn instantiate_scene(
+    mut commands: Commands,
+    mut document: ResMut<Document>,
+    mut placed_entities: ResMut<PlacedEntities>,
+    mut selection: ResMut<SelectionState>,
+    mut control: SimulationControl,
+    mut snapshot: ResMut<SimulationSnapshot>,
+    mut clock: ResMut<SimulationClock>,
+    mut history: ResMut<EditHistory>,
+) {
+    if !document.needs_rebuild {
+        return;
+    }
+    document.needs_rebuild = false;
+    history.clear();
+    control.state.is_running = false;
+    control.recording.clear(Vec::new());
+    control.playback.go_live();
+    snapshot.entities = None;
+    clock.0 = 0.0;
+
+    for entity in placed_entities.entities.drain(..) {
+        commands.entity(entity).despawn();
+    }
+    selection.clear();
+
+    for data in &document.data.entities {
+        let entity = spawn_scene_entity(&mut commands, data);
+        placed_entities.entities.push(entity);
+    }
+}
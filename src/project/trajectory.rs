@@ -0,0 +1,186 @@
+use std::io::Write;
+use std::path::Path;
+
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+
+use super::error::ProjectError;
+use crate::app::{BodyFrame, Frame};
+
+/// State of one entity at one sampled time.
+#[derive(serde::Serialize, Clone, Copy)]
+pub struct TrajectorySample {
+    pub time: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub acceleration: Vec2,
+    pub kinetic_energy: f32,
+    pub field_force: Vec2,
+}
+
+impl TrajectorySample {
+    pub fn from_frame(time: f32, body: &BodyFrame) -> Self {
+        Self {
+            time,
+            position: body.position,
+            velocity: body.linear_velocity,
+            acceleration: body.acceleration,
+            kinetic_energy: body.kinetic_energy,
+            field_force: body.field_force,
+        }
+    }
+}
+
+#[derive(serde::Serialize)]
+pub struct Trajectory {
+    pub name: String,
+    pub samples: Vec<TrajectorySample>,
+}
+
+/// Time series of every entity in a run, in entity order.
+#[derive(serde::Serialize)]
+pub struct TrajectoryData {
+    /// Seconds between samples, 0 when every step was kept.
+    pub interval: f32,
+    pub entities: Vec<Trajectory>,
+}
+
+impl TrajectoryData {
+    /// Samples `frames` every `interval` seconds of simulation time, starting
+    /// with the first frame.
+    pub fn from_frames<'a>(
+        frames: impl IntoIterator<Item = &'a Frame>,
+        names: &EntityHashMap<String>,
+        interval: f32,
+    ) -> Self {
+        let mut series: EntityHashMap<Vec<TrajectorySample>> = EntityHashMap::default();
+        // Tolerates the rounding in the accumulated step times.
+        let slack = interval.max(0.0) * 1e-3;
+        let mut next_time = None;
+        for frame in frames {
+            if next_time.is_some_and(|next| frame.time + slack < next) {
+                continue;
+            }
+            let next = next_time.get_or_insert(frame.time);
+            while interval > 0.0 && *next <= frame.time + slack {
+                *next += interval;
+            }
+            for (entity, body) in &frame.bodies {
+                series
+                    .entry(*entity)
+                    .or_default()
+                    .push(TrajectorySample::from_frame(frame.time, body));
+            }
+        }
+        Self::from_series(series, names, interval)
+    }
+
+    pub fn from_series(
+        series: EntityHashMap<Vec<TrajectorySample>>,
+        names: &EntityHashMap<String>,
+        interval: f32,
+    ) -> Self {
+        let mut series: Vec<_> = series.into_iter().collect();
+        series.sort_by_key(|(entity, _)| *entity);
+        let entities = series
+            .into_iter()
+            .map(|(entity, samples)| Trajectory {
+                name: names.get(&entity).cloned().unwrap_or_default(),
+                samples,
+            })
+            .collect();
+        Self {
+            interval: interval.max(0.0),
+            entities,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrajectoryFormat {
+    Csv,
+    Json,
+}
+
+impl TrajectoryFormat {
+    pub fn from_path(path: &Path) -> Result<Self, ProjectError> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("csv") => Ok(TrajectoryFormat::Csv),
+            Some("json") => Ok(TrajectoryFormat::Json),
+            extension => Err(ProjectError::UnsupportedFormat(
+                extension.map(str::to_string),
+            )),
+        }
+    }
+}
+
+/// One row per entity and sample, for spreadsheets.
+pub fn write_trajectories_csv(
+    data: &TrajectoryData,
+    writer: &mut impl Write,
+) -> Result<(), ProjectError> {
+    writeln!(
+        writer,
+        "name,time,x,y,vx,vy,ax,ay,kinetic_energy,field_force_x,field_force_y"
+    )?;
+    for trajectory in &data.entities {
+        let name = csv_field(&trajectory.name);
+        for sample in &trajectory.samples {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{},{}",
+                name,
+                sample.time,
+                sample.position.x,
+                sample.position.y,
+                sample.velocity.x,
+                sample.velocity.y,
+                sample.acceleration.x,
+                sample.acceleration.y,
+                sample.kinetic_energy,
+                sample.field_force.x,
+                sample.field_force.y,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn write_trajectories_json(
+    data: &TrajectoryData,
+    writer: &mut impl Write,
+) -> Result<(), ProjectError> {
+    serde_json::to_writer_pretty(&mut *writer, data).map_err(|e| ProjectError::Serialize {
+        format: "JSON",
+        message: e.to_string(),
+    })?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+pub fn write_trajectories(
+    data: &TrajectoryData,
+    format: TrajectoryFormat,
+    writer: &mut impl Write,
+) -> Result<(), ProjectError> {
+    match format {
+        TrajectoryFormat::Csv => write_trajectories_csv(data, writer),
+        TrajectoryFormat::Json => write_trajectories_json(data, writer),
+    }
+}
+
+pub fn export_trajectories(path: &Path, data: &TrajectoryData) -> Result<(), ProjectError> {
+    let format = TrajectoryFormat::from_path(path)?;
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_trajectories(data, format, &mut writer)?;
+    writer.flush()?;
+    Ok(())
+}
+
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
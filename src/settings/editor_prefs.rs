@@ -9,6 +9,12 @@ pub struct EditorPrefs {
     pub default_entity_size: Vec2,
     pub show_grid: bool,
     pub show_field_vectors: bool,
+    /// Seconds between exported trajectory samples, 0 exports every step.
+    #[serde(default)]
+    pub trajectory_interval: f32,
+    /// Rotate gizmo steps in 15° increments.
+    #[serde(default)]
+    pub snap_rotation: bool,
 }
 
 impl Default for EditorPrefs {
@@ -20,6 +26,8 @@ impl Default for EditorPrefs {
             default_entity_size: Vec2::new(50.0, 50.0),
             show_grid: true,
             show_field_vectors: false,
+            trajectory_interval: 0.0,
+            snap_rotation: false,
         }
     }
 }
@@ -5,21 +5,6 @@ use super::utils::{
     generate_cross_vertices, generate_regular_polygon_vertices, generate_star_vertices,
 };
 
-#[derive(Component)]
-pub struct InitialState {
-    pub transform: Transform,
-    pub velocity: Vec2,
-}
-
-impl InitialState {
-    pub fn new(transform: Transform, velocity: Vec2) -> Self {
-        Self {
-            transform,
-            velocity,
-        }
-    }
-}
-
 #[derive(
     Default,
     Clone,
@@ -78,6 +63,12 @@ impl EntityShape {
         }
     }
 
+    /// Whether the collider only follows the larger side of its size, so
+    /// resizing should keep both sides equal.
+    pub fn is_uniform(&self) -> bool {
+        !matches!(self, EntityShape::Rectangle | EntityShape::Cross)
+    }
+
     pub fn display_name(&self) -> &'static str {
         match self {
             EntityShape::Rectangle => "Rectangle",
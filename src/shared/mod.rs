@@ -2,7 +2,7 @@ pub mod entity_shape;
 pub mod theme;
 pub mod utils;
 
-pub use entity_shape::{EntityShape, InitialState};
+pub use entity_shape::EntityShape;
 pub use theme::*;
 pub use utils::{
     WorldMousePosition, generate_arc_vertices, generate_circle_vertices, generate_cross_vertices,
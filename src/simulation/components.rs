@@ -1,15 +1,20 @@
 use bevy::prelude::*;
 
+use super::profile::FieldProfile;
+use super::waveform::FieldModulation;
+
 #[derive(Component, Copy, Clone)]
 pub struct Magnetic;
 
 #[derive(Component, Copy, Clone)]
 pub struct Electric;
 
-#[derive(Component, Debug, Clone, Copy)]
+#[derive(Component, Debug, Clone)]
 pub struct Field {
     pub strength: f32,
     pub direction: Vec3,
+    pub profile: FieldProfile,
+    pub modulation: FieldModulation,
 }
 
 impl Field {
@@ -17,11 +22,29 @@ impl Field {
         Self {
             strength,
             direction,
+            profile: FieldProfile::Uniform,
+            modulation: FieldModulation::default(),
         }
     }
+
+    pub fn with_profile(mut self, profile: FieldProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    /// Field vector at a world position and simulation time, for a region
+    /// centred at `center` with `rotation`.
+    pub fn sample(&self, center: Vec2, rotation: Rot2, position: Vec2, time: f32) -> Vec3 {
+        let local = rotation.inverse() * (position - center);
+        let strength = self.strength * self.modulation.strength.value(time);
+        let value = self.profile.sample(strength, self.direction, local);
+        let rotation = rotation * Rot2::radians(self.modulation.rotation.value(time));
+        (rotation * value.xy()).extend(value.z)
+    }
 }
 
 #[derive(Component, Copy, Clone)]
+#[require(LocalField)]
 pub struct Charge {
     pub value: f32,
 }
@@ -31,3 +54,19 @@ impl Charge {
         Self { value }
     }
 }
+
+/// Total electric and magnetic field at a charged body, summed over every
+/// field region it is inside. Rebuilt every physics step.
+#[derive(Component, Debug, Default, Clone, Copy)]
+pub struct LocalField {
+    pub electric: Vec3,
+    pub magnetic: Vec3,
+}
+
+impl LocalField {
+    /// Lorentz force `q (E + v × B)`, projected onto the plane.
+    pub fn lorentz_force(&self, charge: f32, velocity: Vec2) -> Vec2 {
+        let force = self.electric + velocity.extend(0.0).cross(self.magnetic);
+        force.xy() * charge
+    }
+}
@@ -0,0 +1,211 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::components::{Charge, Field};
+use super::resources::{
+    ConservationDiagnostics, ConservationSettings, ConservedQuantities, CoulombSettings,
+    GravityMode, GravitySettings, PhysicsSettings, SimulationClock,
+};
+
+/// Potential of the softened inverse-square force in `inverse_square_force`.
+fn softened_potential(distance_sq: f32, coupling: f32, softening: f32) -> f32 {
+    let dist_sq = distance_sq + softening * softening;
+    if dist_sq <= f32::EPSILON {
+        return 0.0;
+    }
+    coupling / dist_sq.sqrt()
+}
+
+pub fn conservation_enabled(settings: Res<ConservationSettings>) -> bool {
+    settings.enabled
+}
+
+/// Drift relative to the baseline magnitude, or to the current one when the
+/// run started from rest.
+fn relative(drift: f32, baseline_scale: f32, current_scale: f32) -> f32 {
+    let scale = if baseline_scale > f32::EPSILON {
+        baseline_scale
+    } else {
+        current_scale
+    };
+    if scale > f32::EPSILON {
+        drift / scale
+    } else {
+        0.0
+    }
+}
+
+/// Sums energy and momentum over every body every `sample_every` steps and
+/// compares them with the first measurement of the run.
+pub fn measure_conservation(
+    settings: Res<ConservationSettings>,
+    gravity: Res<GravitySettings>,
+    coulomb: Res<CoulombSettings>,
+    physics: Res<PhysicsSettings>,
+    clock: Res<SimulationClock>,
+    mut diagnostics: ResMut<ConservationDiagnostics>,
+    bodies: Query<
+        (
+            Entity,
+            &RigidBody,
+            &Position,
+            Option<&LinearVelocity>,
+            Option<&AngularVelocity>,
+            &ComputedMass,
+            Option<&Mass>,
+            &ComputedAngularInertia,
+            Option<&ConstantForce>,
+        ),
+        Without<Sensor>,
+    >,
+    fields: Query<&Field>,
+    charges: Query<(Entity, &Charge, &Position), Without<Sensor>>,
+) {
+    // Reset and opening a scene both rewind the clock.
+    if clock.0 < diagnostics.last_time {
+        diagnostics.reset_baseline();
+    }
+    diagnostics.last_time = clock.0;
+
+    diagnostics.steps += 1;
+    if diagnostics.baseline.is_some() && diagnostics.steps < settings.sample_every.max(1) {
+        return;
+    }
+    diagnostics.steps = 0;
+
+    let mut external = (gravity.mode == GravityMode::Uniform && gravity.uniform != Vec2::ZERO)
+        || physics.linear_damping > 0.0
+        || physics.angular_damping > 0.0
+        || fields.iter().any(|field| field.strength != 0.0);
+
+    let mut totals = ConservedQuantities::default();
+    let mut scales = ConservedQuantities::default();
+
+    let mut masses = Vec::new();
+    for (
+        entity,
+        rigid_body,
+        position,
+        linear_velocity,
+        angular_velocity,
+        computed,
+        mass,
+        inertia,
+        constant_force,
+    ) in &bodies
+    {
+        if !rigid_body.is_dynamic() {
+            // Collisions with it exchange momentum with the rest of the world.
+            external = true;
+            continue;
+        }
+        external |= constant_force.is_some_and(|force| force.0 != Vec2::ZERO);
+        let mass = mass.map_or(computed.value(), |m| m.0);
+        if !mass.is_finite() || mass <= 0.0 {
+            continue;
+        }
+        let velocity = linear_velocity.map(|v| v.0).unwrap_or_default();
+        let angular_velocity = angular_velocity.map(|v| v.0).unwrap_or_default();
+        let momentum = velocity * mass;
+        let spin = inertia.value() * angular_velocity;
+
+        let kinetic = 0.5 * momentum.dot(velocity) + 0.5 * spin * angular_velocity;
+        totals.kinetic_energy += kinetic;
+        scales.kinetic_energy += kinetic;
+        totals.linear_momentum += momentum;
+        scales.linear_momentum += momentum.abs();
+        let angular = position.0.perp_dot(momentum) + spin;
+        totals.angular_momentum += angular;
+        scales.angular_momentum += angular.abs();
+
+        if gravity.mode == GravityMode::Uniform {
+            let potential = -mass * gravity.uniform.dot(position.0);
+            totals.gravitational_energy += potential;
+            scales.gravitational_energy += potential.abs();
+        }
+        masses.push((entity, position.0, mass));
+    }
+
+    if gravity.mode == GravityMode::Mutual {
+        masses.sort_by_key(|(entity, ..)| *entity);
+        for (i, &(_, position_i, mass_i)) in masses.iter().enumerate() {
+            for &(_, position_j, mass_j) in &masses[i + 1..] {
+                let potential = softened_potential(
+                    position_i.distance_squared(position_j),
+                    -gravity.constant * mass_i * mass_j,
+                    gravity.softening,
+                );
+                totals.gravitational_energy += potential;
+                scales.gravitational_energy += potential.abs();
+            }
+        }
+    }
+
+    if coulomb.enabled {
+        let cutoff_sq = coulomb.cutoff.map(|c| c * c);
+        // The force stops at the cutoff, so the potential is shifted to zero
+        // there; otherwise every pair crossing it would jump in energy.
+        let shift = |coupling| {
+            cutoff_sq.map_or(0.0, |c| softened_potential(c, coupling, coulomb.softening))
+        };
+        let mut charges: Vec<_> = charges
+            .iter()
+            .filter(|(_, charge, _)| charge.value != 0.0)
+            .map(|(entity, charge, position)| (entity, position.0, charge.value))
+            .collect();
+        charges.sort_by_key(|(entity, ..)| *entity);
+        for (i, &(_, position_i, charge_i)) in charges.iter().enumerate() {
+            for &(_, position_j, charge_j) in &charges[i + 1..] {
+                let distance_sq = position_i.distance_squared(position_j);
+                if cutoff_sq.is_some_and(|c| distance_sq > c) {
+                    continue;
+                }
+                let coupling = coulomb.constant * charge_i * charge_j;
+                let potential =
+                    softened_potential(distance_sq, coupling, coulomb.softening) - shift(coupling);
+                totals.electric_energy += potential;
+                scales.electric_energy += potential.abs();
+            }
+        }
+    }
+
+    diagnostics.current = Some(totals);
+    let Some(baseline) = diagnostics.baseline else {
+        diagnostics.baseline = Some(totals);
+        diagnostics.scales = scales;
+        diagnostics.momentum_conserved = !external;
+        return;
+    };
+    let baseline_scales = diagnostics.scales;
+
+    let was_flagged = diagnostics.flagged(settings.tolerance);
+    diagnostics.momentum_conserved &= !external;
+    diagnostics.energy_drift = relative(
+        (totals.total_energy() - baseline.total_energy()).abs(),
+        baseline_scales.kinetic_energy
+            + baseline_scales.gravitational_energy
+            + baseline_scales.electric_energy,
+        scales.kinetic_energy + scales.gravitational_energy + scales.electric_energy,
+    );
+    diagnostics.momentum_drift = relative(
+        (totals.linear_momentum - baseline.linear_momentum).length(),
+        baseline_scales.linear_momentum.length(),
+        scales.linear_momentum.length(),
+    );
+    diagnostics.angular_momentum_drift = relative(
+        (totals.angular_momentum - baseline.angular_momentum).abs(),
+        baseline_scales.angular_momentum,
+        scales.angular_momentum,
+    );
+
+    if !was_flagged && diagnostics.flagged(settings.tolerance) {
+        warn!(
+            "Conservation drift above {:.1}% at t = {:.3} s (energy {:.2}%, momentum {:.2}%, angular momentum {:.2}%)",
+            settings.tolerance * 100.0,
+            clock.0,
+            diagnostics.energy_drift * 100.0,
+            diagnostics.momentum_drift * 100.0,
+            diagnostics.angular_momentum_drift * 100.0,
+        );
+    }
+}
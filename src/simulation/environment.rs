@@ -0,0 +1,42 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::resources::{PhysicsSettings, SimulationClock};
+
+pub fn sync_physics_settings(
+    settings: Res<PhysicsSettings>,
+    mut length_unit: ResMut<PhysicsLengthUnit>,
+    mut substeps: ResMut<SubstepCount>,
+) {
+    length_unit.0 = settings.length_unit;
+    substeps.0 = settings.substeps.max(1);
+}
+
+/// Gives every rigid body the scene damping, including ones spawned later.
+/// Deterministic runs also show the exact step state instead of easing
+/// between steps at the frame rate.
+pub fn apply_body_settings(
+    settings: Res<PhysicsSettings>,
+    mut commands: Commands,
+    bodies: Query<(Entity, Ref<RigidBody>)>,
+) {
+    let changed = settings.is_changed();
+    for (entity, rigid_body) in &bodies {
+        if changed || rigid_body.is_added() {
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.insert((
+                LinearDamping(settings.linear_damping),
+                AngularDamping(settings.angular_damping),
+            ));
+            if settings.deterministic {
+                entity_commands.insert(NoTransformEasing);
+            } else {
+                entity_commands.remove::<NoTransformEasing>();
+            }
+        }
+    }
+}
+
+pub fn advance_clock(time: Res<Time<Physics>>, mut clock: ResMut<SimulationClock>) {
+    clock.0 += time.delta_secs();
+}
@@ -1,48 +1,106 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
 
-use super::components::{Charge, Electric, Field, Magnetic};
+use super::components::{Charge, Electric, Field, LocalField, Magnetic};
+use super::resources::{ChargeIntegrator, SimulationClock};
 
-pub fn apply_magnetic_force(
-    mut magnets: Query<(&Field, &CollidingEntities), With<Magnetic>>,
-    mut entities: Query<(&Charge, Forces)>,
+pub fn accumulate_fields(
+    fields: Query<
+        (
+            Entity,
+            &Field,
+            &CollidingEntities,
+            &Position,
+            &Rotation,
+            Has<Magnetic>,
+            Has<Electric>,
+        ),
+        Or<(With<Magnetic>, With<Electric>)>,
+    >,
+    mut bodies: Query<(&Position, &mut LocalField), With<Charge>>,
+    clock: Res<SimulationClock>,
 ) {
-    for (field, colliding_entities) in &mut magnets {
-        if colliding_entities.0.is_empty() {
-            return;
-        }
+    for (_, mut local) in &mut bodies {
+        *local = LocalField::default();
+    }
+
+    let time = clock.0;
+
+    // Summed in entity order so every run adds overlapping fields the same way.
+    let mut fields: Vec<_> = fields.iter().collect();
+    fields.sort_by_key(|(entity, ..)| *entity);
+
+    for (_, field, colliding_entities, field_position, field_rotation, magnetic, electric) in fields
+    {
+        let center = field_position.0;
+        let rotation = Rot2::from_sin_cos(field_rotation.sin, field_rotation.cos);
 
         for entity in &colliding_entities.0 {
-            if let Ok((charge, mut forces)) = entities.get_mut(*entity) {
-                let velocity = forces.linear_velocity();
-                let q = charge.value;
-                let b = field.strength;
-
-                let magnetic_force =
-                    field.direction.normalize().cross(velocity.extend(0.0)) * q * b;
-                forces.apply_force(magnetic_force.xy());
+            if let Ok((position, mut local)) = bodies.get_mut(*entity) {
+                let value = field.sample(center, rotation, position.0, time);
+                if magnetic {
+                    local.magnetic += value;
+                }
+                if electric {
+                    local.electric += value;
+                }
             }
         }
     }
 }
 
-pub fn apply_electric_force(
-    mut magnets: Query<(&Field, &CollidingEntities), With<Electric>>,
-    mut entities: Query<(&Charge, Forces)>,
+pub fn apply_lorentz_force(
+    integrator: Res<ChargeIntegrator>,
+    mut bodies: Query<(&Charge, &LocalField, Forces)>,
 ) {
-    for (field, colliding_entities) in &mut magnets {
-        if colliding_entities.0.is_empty() {
-            return;
+    if *integrator != ChargeIntegrator::Explicit {
+        return;
+    }
+
+    for (charge, local, mut forces) in &mut bodies {
+        if charge.value == 0.0 {
+            continue;
         }
+        let velocity = forces.linear_velocity();
+        forces.apply_force(local.lorentz_force(charge.value, velocity));
+    }
+}
 
-        for entity in &colliding_entities.0 {
-            if let Ok((charge, mut forces)) = entities.get_mut(*entity) {
-                let q = charge.value;
-                let b = field.strength;
+/// Boris velocity update: half electric kick, magnetic rotation, half electric kick.
+pub fn boris_push(
+    integrator: Res<ChargeIntegrator>,
+    mut bodies: Query<(
+        &Charge,
+        &LocalField,
+        &ComputedMass,
+        &RigidBody,
+        &mut LinearVelocity,
+    )>,
+    time: Res<Time<Physics>>,
+) {
+    if *integrator != ChargeIntegrator::Boris {
+        return;
+    }
 
-                let electric_force = field.direction.normalize() * q * b;
-                forces.apply_force(electric_force.xy());
-            }
+    let dt = time.delta_secs();
+    if dt <= 0.0 {
+        return;
+    }
+
+    for (charge, local, mass, rigid_body, mut velocity) in &mut bodies {
+        if !rigid_body.is_dynamic() || charge.value == 0.0 {
+            continue;
         }
+
+        let half_kick = charge.value * mass.inverse() * dt / 2.0;
+
+        let v_minus = velocity.0.extend(0.0) + local.electric * half_kick;
+        let t = local.magnetic * half_kick;
+        let s = t * (2.0 / (1.0 + t.length_squared()));
+        let v_prime = v_minus + v_minus.cross(t);
+        let v_plus = v_minus + v_prime.cross(s);
+        let v_new = v_plus + local.electric * half_kick;
+
+        velocity.0 = v_new.xy();
     }
 }
@@ -0,0 +1,189 @@
+use avian2d::prelude::*;
+use bevy::prelude::*;
+
+use super::components::Charge;
+use super::quadtree::QuadTree;
+use super::resources::{
+    CoulombSettings, ForceError, ForceSolver, GravityMode, GravitySettings, SolverDiagnostics,
+    SolverSettings,
+};
+
+const ERROR_SAMPLES: usize = 64;
+
+/// Softened inverse-square force on a body at `delta` from its partner.
+/// A positive `coupling` pushes the bodies apart.
+pub fn inverse_square_force(delta: Vec2, coupling: f32, softening: f32) -> Vec2 {
+    let dist_sq = delta.length_squared() + softening * softening;
+    if dist_sq <= f32::EPSILON {
+        return Vec2::ZERO;
+    }
+    delta * (coupling / (dist_sq * dist_sq.sqrt()))
+}
+
+/// Sums the force on every source from every other source, visiting each pair once.
+pub fn pairwise_forces(
+    sources: &[(Vec2, f32)],
+    cutoff: Option<f32>,
+    mut force: impl FnMut(Vec2, f32, f32) -> Vec2,
+) -> Vec<Vec2> {
+    let cutoff_sq = cutoff.map(|c| c * c);
+    let mut totals = vec![Vec2::ZERO; sources.len()];
+
+    for (i, &(pos_i, value_i)) in sources.iter().enumerate() {
+        for (j, &(pos_j, value_j)) in sources.iter().enumerate().skip(i + 1) {
+            let delta = pos_i - pos_j;
+            if cutoff_sq.is_some_and(|c| delta.length_squared() > c) {
+                continue;
+            }
+            let f = force(delta, value_i, value_j);
+            totals[i] += f;
+            totals[j] -= f;
+        }
+    }
+
+    totals
+}
+
+/// Forces on every source using the configured solver, plus the measured
+/// approximation error when requested.
+pub fn long_range_forces(
+    sources: &[(Vec2, f32)],
+    cutoff: Option<f32>,
+    solver: &SolverSettings,
+    force: impl Fn(Vec2, f32, f32) -> Vec2,
+) -> (Vec<Vec2>, Option<ForceError>) {
+    match solver.solver {
+        ForceSolver::Exact => (pairwise_forces(sources, cutoff, &force), None),
+        ForceSolver::BarnesHut => {
+            let tree = QuadTree::build(sources);
+            let totals: Vec<Vec2> = (0..sources.len())
+                .map(|index| tree.force_on(index, solver.theta, cutoff, &force))
+                .collect();
+            let error = solver
+                .measure_error
+                .then(|| measure_error(sources, &totals, cutoff, &force));
+            (totals, error)
+        }
+    }
+}
+
+/// Relative error of `approximate` against the exact sum, over an evenly spaced sample of bodies.
+fn measure_error(
+    sources: &[(Vec2, f32)],
+    approximate: &[Vec2],
+    cutoff: Option<f32>,
+    force: &impl Fn(Vec2, f32, f32) -> Vec2,
+) -> ForceError {
+    let cutoff_sq = cutoff.map(|c| c * c);
+    let step = (sources.len() / ERROR_SAMPLES).max(1);
+    let mut error = ForceError::default();
+    let mut sum = 0.0;
+
+    for index in (0..sources.len()).step_by(step) {
+        let (position, value) = sources[index];
+        let exact: Vec2 = sources
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .map(|(_, (other_position, other_value))| (position - *other_position, *other_value))
+            .filter(|(delta, _)| !cutoff_sq.is_some_and(|c| delta.length_squared() > c))
+            .map(|(delta, other_value)| force(delta, value, other_value))
+            .sum();
+
+        let magnitude = exact.length();
+        if magnitude <= f32::EPSILON {
+            continue;
+        }
+        let relative = (approximate[index] - exact).length() / magnitude;
+        sum += relative;
+        error.max_relative = error.max_relative.max(relative);
+        error.samples += 1;
+    }
+
+    if error.samples > 0 {
+        error.mean_relative = sum / error.samples as f32;
+    }
+    error
+}
+
+pub fn apply_coulomb_force(
+    settings: Res<CoulombSettings>,
+    solver: Res<SolverSettings>,
+    mut diagnostics: ResMut<SolverDiagnostics>,
+    charges: Query<(Entity, &Charge, &Position), Without<Sensor>>,
+    mut bodies: Query<Forces>,
+) {
+    diagnostics.coulomb = None;
+    if !settings.enabled {
+        return;
+    }
+
+    let mut charges: Vec<_> = charges
+        .iter()
+        .filter(|(_, charge, _)| charge.value != 0.0)
+        .map(|(entity, charge, position)| (entity, (position.0, charge.value)))
+        .collect();
+    charges.sort_by_key(|(entity, _)| *entity);
+    let (entities, sources): (Vec<Entity>, Vec<(Vec2, f32)>) = charges.into_iter().unzip();
+
+    if sources.len() < 2 {
+        return;
+    }
+
+    let (totals, error) = long_range_forces(&sources, settings.cutoff, &solver, |delta, q1, q2| {
+        inverse_square_force(delta, settings.constant * q1 * q2, settings.softening)
+    });
+    diagnostics.coulomb = error;
+
+    for (entity, force) in entities.into_iter().zip(totals) {
+        if let Ok(mut forces) = bodies.get_mut(entity) {
+            forces.apply_force(force);
+        }
+    }
+}
+
+pub fn apply_mutual_gravity(
+    settings: Res<GravitySettings>,
+    solver: Res<SolverSettings>,
+    mut diagnostics: ResMut<SolverDiagnostics>,
+    masses: Query<(Entity, &Position, &ComputedMass, Option<&Mass>), Without<Sensor>>,
+    mut bodies: Query<Forces>,
+) {
+    diagnostics.gravity = None;
+    if settings.mode != GravityMode::Mutual {
+        return;
+    }
+
+    let mut masses: Vec<_> = masses
+        .iter()
+        .map(|(entity, position, computed, mass)| {
+            let mass = mass.map_or(computed.value(), |m| m.0);
+            (entity, (position.0, mass))
+        })
+        .filter(|(_, (_, mass))| mass.is_finite() && *mass > 0.0)
+        .collect();
+    masses.sort_by_key(|(entity, _)| *entity);
+    let (entities, sources): (Vec<Entity>, Vec<(Vec2, f32)>) = masses.into_iter().unzip();
+
+    if sources.len() < 2 {
+        return;
+    }
+
+    let (totals, error) = long_range_forces(&sources, None, &solver, |delta, m1, m2| {
+        inverse_square_force(delta, -settings.constant * m1 * m2, settings.softening)
+    });
+    diagnostics.gravity = error;
+
+    for (entity, force) in entities.into_iter().zip(totals) {
+        if let Ok(mut forces) = bodies.get_mut(entity) {
+            forces.apply_force(force);
+        }
+    }
+}
+
+pub fn sync_uniform_gravity(settings: Res<GravitySettings>, mut gravity: ResMut<Gravity>) {
+    gravity.0 = match settings.mode {
+        GravityMode::Uniform => settings.uniform,
+        GravityMode::Mutual | GravityMode::None => Vec2::ZERO,
+    };
+}
@@ -1,6 +1,16 @@
 pub mod components;
+pub mod conservation;
+pub mod environment;
 pub mod field;
+pub mod interaction;
 pub mod plugin;
+pub mod profile;
+pub mod quadtree;
+pub mod resources;
+pub mod waveform;
 
 pub use components::*;
 pub use plugin::SimulationPlugin;
+pub use profile::{FieldGrid, FieldProfile};
+pub use resources::*;
+pub use waveform::{FieldModulation, Waveform};
@@ -1,7 +1,11 @@
 use avian2d::prelude::*;
 use bevy::prelude::*;
 
-use super::field;
+use super::resources::{
+    ChargeIntegrator, ConservationDiagnostics, ConservationSettings, CoulombSettings,
+    GravitySettings, PhysicsSettings, SimulationClock, SolverDiagnostics, SolverSettings,
+};
+use super::{conservation, environment, field, interaction};
 use crate::app::SimulationState;
 
 pub struct SimulationPlugin;
@@ -10,24 +14,67 @@ impl Plugin for SimulationPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(
             PhysicsPlugins::default()
-                .with_length_unit(10.0)
+                .with_length_unit(PhysicsSettings::default().length_unit)
                 .set(PhysicsInterpolationPlugin::interpolate_all()),
         )
-        .add_plugins(PhysicsDebugPlugin::default())
-        // .insert_gizmo_config(PhysicsGizmos::all(), GizmoConfig::default())
-        .insert_resource(Gravity(Vec2::NEG_Y * 100.0))
+        .init_resource::<GravitySettings>()
+        .init_resource::<CoulombSettings>()
+        .init_resource::<SolverSettings>()
+        .init_resource::<SolverDiagnostics>()
+        .init_resource::<ConservationSettings>()
+        .init_resource::<ConservationDiagnostics>()
+        .init_resource::<ChargeIntegrator>()
+        .init_resource::<PhysicsSettings>()
+        .init_resource::<SimulationClock>()
         .add_systems(
             PhysicsSchedule,
-            (field::apply_magnetic_force, field::apply_electric_force)
+            (
+                environment::advance_clock,
+                field::accumulate_fields,
+                field::apply_lorentz_force,
+                field::boris_push,
+                interaction::apply_coulomb_force,
+                interaction::apply_mutual_gravity,
+            )
                 .chain()
                 .before(PhysicsStepSystems::First),
         )
-        .add_systems(Update, update_simulation);
+        .add_systems(
+            PhysicsSchedule,
+            conservation::measure_conservation
+                .run_if(conservation::conservation_enabled)
+                .after(PhysicsStepSystems::Last),
+        )
+        .add_systems(
+            Update,
+            (
+                update_simulation,
+                interaction::sync_uniform_gravity.run_if(resource_changed::<GravitySettings>),
+                environment::sync_physics_settings.run_if(resource_changed::<PhysicsSettings>),
+                environment::apply_body_settings,
+            ),
+        );
     }
 }
 
-fn update_simulation(state: Res<SimulationState>, mut physics_time: ResMut<Time<Physics>>) {
+fn update_simulation(
+    state: Res<SimulationState>,
+    settings: Res<PhysicsSettings>,
+    mut physics_time: ResMut<Time<Physics>>,
+    mut fixed_time: ResMut<Time<Fixed>>,
+) {
     physics_time.set_relative_speed(state.time_scale);
+    if settings.timestep_hz > 0.0 && state.time_scale > 0.0 {
+        // Physics steps last the fixed timestep times the time scale. In
+        // deterministic mode a shorter fixed timestep cancels the scale, so
+        // steps keep their length and only run more or less often.
+        let timestep_hz = if settings.deterministic {
+            settings.timestep_hz * f64::from(state.time_scale)
+        } else {
+            settings.timestep_hz
+        };
+        fixed_time.set_timestep_hz(timestep_hz);
+    }
     if state.is_running {
         physics_time.unpause();
     } else {
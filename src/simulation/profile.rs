@@ -0,0 +1,185 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+
+/// Spatial shape of a field, evaluated in the field's local frame.
+#[derive(Default, Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum FieldProfile {
+    /// Same value everywhere inside the region.
+    #[default]
+    Uniform,
+    /// Inverse-square field radiating from the region centre, like a point charge.
+    Radial { softening: f32 },
+    /// Straight current through the region centre along `Field::direction`,
+    /// falling off as 1/r from the wire.
+    Wire { softening: f32 },
+    /// Strength changes linearly with the local offset.
+    Gradient { gradient: Vec2 },
+    /// Values sampled on a regular grid and bilinearly interpolated.
+    Grid(FieldGrid),
+}
+
+impl FieldProfile {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            FieldProfile::Uniform => "Uniform",
+            FieldProfile::Radial { .. } => "Radial",
+            FieldProfile::Wire { .. } => "Wire",
+            FieldProfile::Gradient { .. } => "Gradient",
+            FieldProfile::Grid(_) => "Grid",
+        }
+    }
+
+    /// Field vector at `local`, the offset from the region centre.
+    pub fn sample(&self, strength: f32, direction: Vec3, local: Vec2) -> Vec3 {
+        let axis = direction.normalize_or_zero();
+        match self {
+            FieldProfile::Uniform => axis * strength,
+            FieldProfile::Radial { softening } => {
+                let dist_sq = local.length_squared() + softening * softening;
+                if dist_sq <= f32::EPSILON {
+                    return Vec3::ZERO;
+                }
+                local.extend(0.0) * (strength / (dist_sq * dist_sq.sqrt()))
+            }
+            FieldProfile::Wire { softening } => {
+                let offset = local.extend(0.0);
+                let perpendicular = offset - axis * offset.dot(axis);
+                let dist_sq = perpendicular.length_squared() + softening * softening;
+                if dist_sq <= f32::EPSILON {
+                    return Vec3::ZERO;
+                }
+                axis.cross(perpendicular) * (strength / dist_sq)
+            }
+            FieldProfile::Gradient { gradient } => axis * (strength + gradient.dot(local)),
+            FieldProfile::Grid(grid) => grid.sample(local) * strength,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldGrid {
+    /// Local position of the first sample.
+    pub origin: Vec2,
+    pub cell_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Row-major samples, `columns * rows` long.
+    pub values: Vec<Vec3>,
+    /// File the samples were loaded from, kept for reference only.
+    pub source: Option<String>,
+}
+
+impl FieldGrid {
+    pub fn sample(&self, local: Vec2) -> Vec3 {
+        if self.columns == 0 || self.rows == 0 {
+            return Vec3::ZERO;
+        }
+
+        let cell = (local - self.origin) / self.cell_size;
+        let max = Vec2::new((self.columns - 1) as f32, (self.rows - 1) as f32);
+        if cell.x < 0.0 || cell.y < 0.0 || cell.x > max.x || cell.y > max.y {
+            return Vec3::ZERO;
+        }
+
+        let x0 = (cell.x.floor() as usize).min(self.columns - 1);
+        let y0 = (cell.y.floor() as usize).min(self.rows - 1);
+        let x1 = (x0 + 1).min(self.columns - 1);
+        let y1 = (y0 + 1).min(self.rows - 1);
+        let tx = cell.x - x0 as f32;
+        let ty = cell.y - y0 as f32;
+
+        let at = |x: usize, y: usize| self.values[y * self.columns + x];
+        let bottom = at(x0, y0).lerp(at(x1, y0), tx);
+        let top = at(x0, y1).lerp(at(x1, y1), tx);
+        bottom.lerp(top, ty)
+    }
+
+    /// Reads a CSV of `x,y,fx,fy[,fz]` rows covering a regular grid, in any order.
+    /// Coordinates are local to the field centre.
+    pub fn load_csv(path: &Path) -> Result<Self, String> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+        let mut points = Vec::new();
+        for (line_number, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let numbers: Result<Vec<f32>, _> =
+                line.split(',').map(|v| v.trim().parse::<f32>()).collect();
+            let numbers = match numbers {
+                Ok(numbers) => numbers,
+                // Header row
+                Err(_) if points.is_empty() => continue,
+                Err(e) => return Err(format!("Line {}: {}", line_number + 1, e)),
+            };
+            match numbers.as_slice() {
+                [x, y, fx, fy] => points.push((Vec2::new(*x, *y), Vec3::new(*fx, *fy, 0.0))),
+                [x, y, fx, fy, fz] => points.push((Vec2::new(*x, *y), Vec3::new(*fx, *fy, *fz))),
+                _ => {
+                    return Err(format!("Line {}: expected 4 or 5 columns", line_number + 1));
+                }
+            }
+        }
+
+        let axis = |select: fn(&Vec2) -> f32| {
+            let mut values: Vec<f32> = points.iter().map(|(p, _)| select(p)).collect();
+            values.sort_by(f32::total_cmp);
+            values.dedup();
+            values
+        };
+        let xs = axis(|p| p.x);
+        let ys = axis(|p| p.y);
+
+        if xs.len() < 2 || ys.len() < 2 || xs.len() * ys.len() != points.len() {
+            return Err("Samples do not form a complete regular grid".to_string());
+        }
+
+        // Relative to the spacing, to allow for rounding in the file.
+        let evenly_spaced = |values: &[f32]| {
+            let step = values[1] - values[0];
+            values
+                .windows(2)
+                .all(|pair| ((pair[1] - pair[0]) - step).abs() <= step * 1e-3)
+        };
+        if !evenly_spaced(&xs) || !evenly_spaced(&ys) {
+            return Err("Samples are not evenly spaced".to_string());
+        }
+
+        let origin = Vec2::new(xs[0], ys[0]);
+        let columns = xs.len();
+        let rows = ys.len();
+        let cell_size = Vec2::new(
+            (xs[columns - 1] - xs[0]) / (columns - 1) as f32,
+            (ys[rows - 1] - ys[0]) / (rows - 1) as f32,
+        );
+
+        let mut values = vec![Vec3::ZERO; columns * rows];
+        let mut written = vec![false; columns * rows];
+        for (position, value) in points {
+            // Both axes hold every coordinate that occurs, so the lookups succeed.
+            let x = xs.partition_point(|v| *v < position.x);
+            let y = ys.partition_point(|v| *v < position.y);
+            let index = y * columns + x;
+            if written[index] {
+                return Err(format!(
+                    "Sample at ({}, {}) appears more than once",
+                    position.x, position.y
+                ));
+            }
+            written[index] = true;
+            values[index] = value;
+        }
+
+        Ok(Self {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            values,
+            source: Some(path.display().to_string()),
+        })
+    }
+}
@@ -0,0 +1,225 @@
+use bevy::prelude::*;
+
+const LEAF_CAPACITY: usize = 4;
+const MAX_DEPTH: usize = 24;
+
+/// Aggregate of all sources with the same sign inside a node.
+/// Positive and negative sources are kept apart so that a neutral cluster
+/// still has a meaningful centre for each polarity.
+#[derive(Default, Clone, Copy)]
+struct Moment {
+    total: f32,
+    weighted_position: Vec2,
+}
+
+impl Moment {
+    fn add(&mut self, position: Vec2, value: f32) {
+        self.total += value;
+        self.weighted_position += position * value;
+    }
+
+    fn center(&self) -> Vec2 {
+        self.weighted_position / self.total
+    }
+}
+
+struct Node {
+    center: Vec2,
+    half_size: f32,
+    children: Option<[usize; 4]>,
+    bodies: Vec<usize>,
+    positive: Moment,
+    negative: Moment,
+}
+
+impl Node {
+    fn new(center: Vec2, half_size: f32) -> Self {
+        Self {
+            center,
+            half_size,
+            children: None,
+            bodies: Vec::new(),
+            positive: Moment::default(),
+            negative: Moment::default(),
+        }
+    }
+
+    fn quadrant(&self, position: Vec2) -> usize {
+        let right = (position.x >= self.center.x) as usize;
+        let top = (position.y >= self.center.y) as usize;
+        right | (top << 1)
+    }
+}
+
+/// Barnes–Hut quadtree over point sources such as charges or masses.
+pub struct QuadTree<'a> {
+    sources: &'a [(Vec2, f32)],
+    nodes: Vec<Node>,
+}
+
+impl<'a> QuadTree<'a> {
+    pub fn build(sources: &'a [(Vec2, f32)]) -> Self {
+        let mut min = Vec2::splat(f32::MAX);
+        let mut max = Vec2::splat(f32::MIN);
+        for (position, _) in sources {
+            min = min.min(*position);
+            max = max.max(*position);
+        }
+        if sources.is_empty() {
+            min = Vec2::ZERO;
+            max = Vec2::ZERO;
+        }
+
+        let center = (min + max) / 2.0;
+        let half_size = ((max - min).max_element() / 2.0).max(1.0) * 1.01;
+
+        let mut tree = Self {
+            sources,
+            nodes: vec![Node::new(center, half_size)],
+        };
+        for index in 0..sources.len() {
+            tree.insert(0, index, 0);
+        }
+        tree
+    }
+
+    fn insert(&mut self, node: usize, index: usize, depth: usize) {
+        let (position, value) = self.sources[index];
+        if value >= 0.0 {
+            self.nodes[node].positive.add(position, value);
+        } else {
+            self.nodes[node].negative.add(position, value);
+        }
+
+        if let Some(children) = self.nodes[node].children {
+            let child = children[self.nodes[node].quadrant(position)];
+            self.insert(child, index, depth + 1);
+            return;
+        }
+
+        self.nodes[node].bodies.push(index);
+        if self.nodes[node].bodies.len() > LEAF_CAPACITY && depth < MAX_DEPTH {
+            self.subdivide(node, depth);
+        }
+    }
+
+    fn subdivide(&mut self, node: usize, depth: usize) {
+        let center = self.nodes[node].center;
+        let quarter = self.nodes[node].half_size / 2.0;
+
+        let first = self.nodes.len();
+        for quadrant in 0..4 {
+            let offset = Vec2::new(
+                if quadrant & 1 == 1 { quarter } else { -quarter },
+                if quadrant & 2 == 2 { quarter } else { -quarter },
+            );
+            self.nodes.push(Node::new(center + offset, quarter));
+        }
+        self.nodes[node].children = Some([first, first + 1, first + 2, first + 3]);
+
+        let bodies = std::mem::take(&mut self.nodes[node].bodies);
+        for index in bodies {
+            let (position, value) = self.sources[index];
+            let child = first + self.nodes[node].quadrant(position);
+            // Moments of `node` already include these bodies, only the child needs them.
+            if value >= 0.0 {
+                self.nodes[child].positive.add(position, value);
+            } else {
+                self.nodes[child].negative.add(position, value);
+            }
+            self.nodes[child].bodies.push(index);
+        }
+
+        for child in first..first + 4 {
+            if self.nodes[child].bodies.len() > LEAF_CAPACITY && depth + 1 < MAX_DEPTH {
+                self.subdivide(child, depth + 1);
+            }
+        }
+    }
+
+    /// Approximate force on source `index`. A node is treated as a single
+    /// source when `node size / distance < theta`; `theta = 0` gives the exact sum.
+    pub fn force_on(
+        &self,
+        index: usize,
+        theta: f32,
+        cutoff: Option<f32>,
+        force: &impl Fn(Vec2, f32, f32) -> Vec2,
+    ) -> Vec2 {
+        let cutoff_sq = cutoff.map(|c| c * c);
+        let mut total = Vec2::ZERO;
+        let mut stack = vec![0];
+
+        while let Some(node) = stack.pop() {
+            let node = &self.nodes[node];
+            if node.children.is_none() {
+                total += self.direct(index, &node.bodies, cutoff_sq, force);
+                continue;
+            }
+
+            if self.is_far(index, node, theta) {
+                total += self.approximate(index, &node.positive, cutoff_sq, force);
+                total += self.approximate(index, &node.negative, cutoff_sq, force);
+            } else if let Some(children) = node.children {
+                stack.extend(children);
+            }
+        }
+
+        total
+    }
+
+    fn is_far(&self, index: usize, node: &Node, theta: f32) -> bool {
+        let (position, _) = self.sources[index];
+        let inside = (position - node.center).abs().max_element() <= node.half_size;
+        if inside {
+            return false;
+        }
+
+        let size = node.half_size * 2.0;
+        let total = node.positive.total - node.negative.total;
+        let center = (node.positive.weighted_position - node.negative.weighted_position) / total;
+        let distance = (position - center).length();
+        distance > 0.0 && size / distance < theta
+    }
+
+    fn direct(
+        &self,
+        index: usize,
+        bodies: &[usize],
+        cutoff_sq: Option<f32>,
+        force: &impl Fn(Vec2, f32, f32) -> Vec2,
+    ) -> Vec2 {
+        let (position, value) = self.sources[index];
+        let mut total = Vec2::ZERO;
+        for &other in bodies {
+            if other == index {
+                continue;
+            }
+            let (other_position, other_value) = self.sources[other];
+            let delta = position - other_position;
+            if cutoff_sq.is_some_and(|c| delta.length_squared() > c) {
+                continue;
+            }
+            total += force(delta, value, other_value);
+        }
+        total
+    }
+
+    fn approximate(
+        &self,
+        index: usize,
+        moment: &Moment,
+        cutoff_sq: Option<f32>,
+        force: &impl Fn(Vec2, f32, f32) -> Vec2,
+    ) -> Vec2 {
+        if moment.total == 0.0 {
+            return Vec2::ZERO;
+        }
+        let (position, value) = self.sources[index];
+        let delta = position - moment.center();
+        if cutoff_sq.is_some_and(|c| delta.length_squared() > c) {
+            return Vec2::ZERO;
+        }
+        force(delta, value, moment.total)
+    }
+}
@@ -0,0 +1,296 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+/// Pairwise electrostatic interaction between every `Charge` body.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CoulombSettings {
+    pub enabled: bool,
+    pub constant: f32,
+    /// Plummer softening length, keeps the force finite when bodies overlap.
+    pub softening: f32,
+    /// Pairs further apart than this are ignored. `None` sums every pair.
+    pub cutoff: Option<f32>,
+}
+
+impl Default for CoulombSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            constant: 100000.0,
+            softening: 5.0,
+            cutoff: None,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum GravityMode {
+    /// Constant acceleration applied by avian's `Gravity` resource.
+    #[default]
+    Uniform,
+    /// Every massive body attracts every other one.
+    Mutual,
+    None,
+}
+
+impl GravityMode {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            GravityMode::Uniform => "Uniform",
+            GravityMode::Mutual => "Mutual",
+            GravityMode::None => "None",
+        }
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct GravitySettings {
+    pub mode: GravityMode,
+    pub uniform: Vec2,
+    /// Gravitational constant used in `GravityMode::Mutual`.
+    pub constant: f32,
+    pub softening: f32,
+}
+
+impl Default for GravitySettings {
+    fn default() -> Self {
+        Self {
+            mode: GravityMode::Uniform,
+            uniform: Vec2::NEG_Y * 100.0,
+            constant: 1000.0,
+            softening: 5.0,
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum ForceSolver {
+    /// Direct O(n²) sum over every pair.
+    #[default]
+    Exact,
+    /// Quadtree approximation, O(n log n).
+    BarnesHut,
+}
+
+impl ForceSolver {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ForceSolver::Exact => "Exact",
+            ForceSolver::BarnesHut => "Barnes-Hut",
+        }
+    }
+}
+
+/// How long-range Coulomb and gravity forces are summed.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SolverSettings {
+    pub solver: ForceSolver,
+    /// Opening angle; smaller is more accurate, 0 degenerates to the exact sum.
+    pub theta: f32,
+    /// Compare the approximation against the exact sum every step.
+    pub measure_error: bool,
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            solver: ForceSolver::Exact,
+            theta: 0.5,
+            measure_error: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ForceError {
+    pub mean_relative: f32,
+    pub max_relative: f32,
+    pub samples: usize,
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SolverDiagnostics {
+    pub coulomb: Option<ForceError>,
+    pub gravity: Option<ForceError>,
+}
+
+/// Energy and momentum bookkeeping, saved with the scene so headless runs
+/// check the same drift as the editor.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct ConservationSettings {
+    pub enabled: bool,
+    /// Relative drift above which a quantity is flagged.
+    pub tolerance: f32,
+    /// Physics steps between measurements, as the potentials cost O(n²).
+    pub sample_every: u32,
+}
+
+impl Default for ConservationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            tolerance: 0.01,
+            sample_every: 1,
+        }
+    }
+}
+
+/// Totals over every body after a physics step. Angular momentum is taken
+/// about the world origin.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConservedQuantities {
+    pub kinetic_energy: f32,
+    /// Uniform or mutual gravity, whichever is active.
+    pub gravitational_energy: f32,
+    pub electric_energy: f32,
+    pub linear_momentum: Vec2,
+    pub angular_momentum: f32,
+}
+
+impl ConservedQuantities {
+    pub fn total_energy(&self) -> f32 {
+        self.kinetic_energy + self.gravitational_energy + self.electric_energy
+    }
+}
+
+/// Drift of the conserved quantities since the first step of the run,
+/// relative to the magnitudes they started from.
+#[derive(Resource, Debug)]
+pub struct ConservationDiagnostics {
+    pub current: Option<ConservedQuantities>,
+    pub baseline: Option<ConservedQuantities>,
+    pub energy_drift: f32,
+    pub momentum_drift: f32,
+    pub angular_momentum_drift: f32,
+    /// Sums of the magnitudes of every contribution at the baseline, used to
+    /// scale the drift when the totals themselves are near zero.
+    pub scales: ConservedQuantities,
+    /// Clock time of the last measurement, to notice resets.
+    pub last_time: f32,
+    /// Whether nothing outside the bodies pushed on them since the baseline:
+    /// no uniform gravity, fields, constant forces, damping or non-dynamic
+    /// bodies to collide with. Momentum drift is only flagged then.
+    pub momentum_conserved: bool,
+    /// Steps since the last measurement.
+    pub steps: u32,
+}
+
+impl Default for ConservationDiagnostics {
+    fn default() -> Self {
+        Self {
+            current: None,
+            baseline: None,
+            energy_drift: 0.0,
+            momentum_drift: 0.0,
+            angular_momentum_drift: 0.0,
+            scales: ConservedQuantities::default(),
+            last_time: 0.0,
+            momentum_conserved: true,
+            steps: 0,
+        }
+    }
+}
+
+impl ConservationDiagnostics {
+    pub fn energy_flagged(&self, tolerance: f32) -> bool {
+        self.energy_drift > tolerance
+    }
+
+    pub fn momentum_flagged(&self, tolerance: f32) -> bool {
+        self.momentum_conserved && self.momentum_drift > tolerance
+    }
+
+    pub fn angular_momentum_flagged(&self, tolerance: f32) -> bool {
+        self.momentum_conserved && self.angular_momentum_drift > tolerance
+    }
+
+    pub fn flagged(&self, tolerance: f32) -> bool {
+        self.energy_flagged(tolerance)
+            || self.momentum_flagged(tolerance)
+            || self.angular_momentum_flagged(tolerance)
+    }
+
+    /// Measures drift from the next step on.
+    pub fn reset_baseline(&mut self) {
+        self.baseline = None;
+        self.momentum_conserved = true;
+        self.steps = 0;
+        self.energy_drift = 0.0;
+        self.momentum_drift = 0.0;
+        self.angular_momentum_drift = 0.0;
+    }
+}
+
+/// Solver and conservation diagnostics, for the panels that show them.
+#[derive(SystemParam)]
+pub struct Diagnostics<'w> {
+    pub solver: Res<'w, SolverDiagnostics>,
+    pub conservation: ResMut<'w, ConservationDiagnostics>,
+}
+
+/// How the field force on `Charge` bodies is integrated.
+#[derive(
+    Resource, Default, Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize,
+)]
+pub enum ChargeIntegrator {
+    /// `q (E + v × B)` is handed to avian as a force.
+    #[default]
+    Explicit,
+    /// Boris rotation of the velocity; keeps gyration radius and kinetic
+    /// energy bounded over long runs in magnetic fields.
+    Boris,
+}
+
+impl ChargeIntegrator {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ChargeIntegrator::Explicit => "Explicit",
+            ChargeIntegrator::Boris => "Boris",
+        }
+    }
+}
+
+/// Simulated seconds since the run started. Unlike `Time<Physics>` it can be
+/// set back when the run is reset or resumed from a recorded frame.
+#[derive(Resource, Debug, Default, Clone, Copy)]
+pub struct SimulationClock(pub f32);
+
+/// Global avian parameters, applied to its resources and to every rigid body.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(default)]
+pub struct PhysicsSettings {
+    /// Typical object size in world units; avian scales its tolerances by it.
+    pub length_unit: f32,
+    pub substeps: u32,
+    /// Fixed physics updates per second.
+    pub timestep_hz: f64,
+    pub linear_damping: f32,
+    pub angular_damping: f32,
+    /// Keep the step length fixed regardless of time scale and frame rate,
+    /// so that a scene always gives the same trajectories.
+    pub deterministic: bool,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            length_unit: 10.0,
+            substeps: 6,
+            timestep_hz: 64.0,
+            linear_damping: 0.0,
+            angular_damping: 0.0,
+            deterministic: false,
+        }
+    }
+}
+
+/// Scene-level simulation settings, grouped so they can be saved and restored together.
+#[derive(SystemParam)]
+pub struct SimulationSettings<'w> {
+    pub gravity: ResMut<'w, GravitySettings>,
+    pub coulomb: ResMut<'w, CoulombSettings>,
+    pub solver: ResMut<'w, SolverSettings>,
+    pub integrator: ResMut<'w, ChargeIntegrator>,
+    pub physics: ResMut<'w, PhysicsSettings>,
+    pub conservation: ResMut<'w, ConservationSettings>,
+}
@@ -0,0 +1,116 @@
+use std::f32::consts::TAU;
+
+/// A scalar function of simulation time in seconds.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum Waveform {
+    Constant(f32),
+    Sine {
+        amplitude: f32,
+        frequency: f32,
+        phase: f32,
+        offset: f32,
+    },
+    /// `high` for the first `duty` fraction of every period after `delay`, `low` otherwise.
+    Pulse {
+        high: f32,
+        low: f32,
+        period: f32,
+        duty: f32,
+        delay: f32,
+    },
+    /// Goes linearly from `from` to `to` over `duration` seconds starting at `start`.
+    Ramp {
+        from: f32,
+        to: f32,
+        start: f32,
+        duration: f32,
+    },
+    /// Piecewise-linear `(time, value)` points, held constant outside the table.
+    Table(Vec<(f32, f32)>),
+}
+
+impl Waveform {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Waveform::Constant(_) => "Constant",
+            Waveform::Sine { .. } => "Sine",
+            Waveform::Pulse { .. } => "Pulse",
+            Waveform::Ramp { .. } => "Ramp",
+            Waveform::Table(_) => "Table",
+        }
+    }
+
+    pub fn value(&self, time: f32) -> f32 {
+        match self {
+            Waveform::Constant(value) => *value,
+            Waveform::Sine {
+                amplitude,
+                frequency,
+                phase,
+                offset,
+            } => offset + amplitude * (TAU * frequency * time + phase).sin(),
+            Waveform::Pulse {
+                high,
+                low,
+                period,
+                duty,
+                delay,
+            } => {
+                if time < *delay || *period <= 0.0 {
+                    return *low;
+                }
+                let cycle = ((time - delay) / period).fract();
+                if cycle < *duty { *high } else { *low }
+            }
+            Waveform::Ramp {
+                from,
+                to,
+                start,
+                duration,
+            } => {
+                if *duration <= 0.0 {
+                    return if time < *start { *from } else { *to };
+                }
+                let t = ((time - start) / duration).clamp(0.0, 1.0);
+                from + (to - from) * t
+            }
+            Waveform::Table(points) => {
+                let Some(&(first_time, first_value)) = points.first() else {
+                    return 0.0;
+                };
+                if time <= first_time {
+                    return first_value;
+                }
+                for pair in points.windows(2) {
+                    let (t0, v0) = pair[0];
+                    let (t1, v1) = pair[1];
+                    if time <= t1 {
+                        if t1 <= t0 {
+                            return v1;
+                        }
+                        return v0 + (v1 - v0) * (time - t0) / (t1 - t0);
+                    }
+                }
+                points.last().map_or(first_value, |(_, value)| *value)
+            }
+        }
+    }
+}
+
+/// Time dependence of a `Field`.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct FieldModulation {
+    /// Multiplies `Field::strength`.
+    pub strength: Waveform,
+    /// Rotates the field vector in the plane, in radians.
+    pub rotation: Waveform,
+}
+
+impl Default for FieldModulation {
+    fn default() -> Self {
+        Self {
+            strength: Waveform::Constant(1.0),
+            rotation: Waveform::Constant(0.0),
+        }
+    }
+}
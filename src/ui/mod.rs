@@ -4,4 +4,4 @@ pub mod resources;
 
 pub use panels::*;
 pub use plugin::UiPlugin;
-pub use resources::{GameViewTab, UiPanelVisibility, UiState};
+pub use resources::{GameViewTab, PlotState, UiPanelVisibility, UiState};
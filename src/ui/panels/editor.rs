@@ -4,8 +4,17 @@ use bevy_egui::egui::{self};
 
 use crate::{
     camera::WorldCamera,
-    editor::{EntityName, FieldType, PlacementState, SelectionState},
-    simulation::components::{Charge, Electric, Field, Magnetic},
+    editor::{
+        EditCommand, EditHistory, EditorEntity, EntityName, FieldType, PlacementState,
+        SelectionState,
+    },
+    project::{SceneEntityQuery, SceneEntityQueryItem},
+    simulation::{
+        ChargeIntegrator, FieldGrid, FieldProfile, ForceError, ForceSolver, SimulationSettings,
+        SolverDiagnostics, Waveform,
+        components::{Charge, Electric, Field, Magnetic},
+    },
+    ui::resources::{PlotQuantity, PlotState},
 };
 
 pub fn editor(
@@ -33,6 +42,11 @@ pub fn editor(
         ),
         Without<WorldCamera>,
     >,
+    settings: &mut SimulationSettings,
+    diagnostics: &SolverDiagnostics,
+    plot: &mut PlotState,
+    scene_query: &Query<SceneEntityQuery, (With<EditorEntity>, Without<WorldCamera>)>,
+    history: &mut EditHistory,
     // mut commands: Commands,
 ) {
     egui::ScrollArea::vertical().show(ui, |ui| {
@@ -173,9 +187,24 @@ pub fn editor(
                                         .speed(0.1),
                                 );
                             });
-                        }
+                            ui.end_row();
 
-                        ui.end_row();
+                            field_profile_rows(ui, "field_profile", &mut placement.field.profile);
+                            waveform_rows(
+                                ui,
+                                "Strength(t):",
+                                "strength_wave",
+                                &mut placement.field.modulation.strength,
+                                1.0,
+                            );
+                            waveform_rows(
+                                ui,
+                                "Rotation(t):",
+                                "rotation_wave",
+                                &mut placement.field.modulation.rotation,
+                                0.0,
+                            );
+                        }
 
                         ui.label("Size:");
                         ui.horizontal(|ui| {
@@ -185,6 +214,15 @@ pub fn editor(
                             ui.add(egui::DragValue::new(&mut placement.size.y).speed(1.0));
                         });
                         ui.end_row();
+
+                        ui.label("Color:");
+                        let srgba = placement.color.to_srgba();
+                        let mut rgb = [srgba.red, srgba.green, srgba.blue];
+                        if ui.color_edit_button_rgb(&mut rgb).changed() {
+                            placement.color = Color::srgb(rgb[0], rgb[1], rgb[2]);
+                        }
+                        ui.end_row();
+
                         let shapes = [
                             ("Rect", crate::shared::EntityShape::Rectangle),
                             ("Circle", crate::shared::EntityShape::Circle),
@@ -208,49 +246,14 @@ pub fn editor(
         egui::CollapsingHeader::new("Properties")
             .default_open(true)
             .show(ui, |ui| {
-                if let Some(selected) = selection.selected_entity {
-                    if let Ok((entity, transform, name, charge, velocity, rigid_body)) =
-                        entity_query.get(selected)
-                    {
-                        let entity_name = name.map(|n| n.0.as_str()).unwrap_or("Unknown");
-
-                        egui::Grid::new("properties_grid")
-                            .num_columns(2)
-                            .spacing([10.0, 5.0])
-                            .show(ui, |ui| {
-                                ui.label("Name:");
-                                ui.label(entity_name);
-                                ui.end_row();
-
-                                ui.label("Entity:");
-                                ui.label(format!("{}", entity.index()));
-                                ui.end_row();
-
-                                ui.label("Position:");
-                                ui.label(format!(
-                                    "({:.1}, {:.1})",
-                                    transform.translation.x, transform.translation.y
-                                ));
-                                ui.end_row();
-
-                                if let Some(vel) = velocity {
-                                    ui.label("Velocity:");
-                                    ui.label(format!("({:.1}, {:.1})", vel.x, vel.y));
-                                    ui.end_row();
-                                }
-
-                                if let Some(rb) = rigid_body {
-                                    ui.label("Body:");
-                                    ui.label(format!("{:?}", rb));
-                                    ui.end_row();
-                                }
-
-                                if let Some(cha) = charge {
-                                    ui.label("Charge:");
-                                    ui.label(format!("{:?}", cha.value));
-                                    ui.end_row();
-                                }
-                            });
+                if selection.entities.len() > 1 {
+                    let items: Vec<_> = scene_query
+                        .iter_many(selection.entities.iter().copied())
+                        .collect();
+                    group_properties(ui, &items, history);
+                } else if let Some(selected) = selection.selected_entity {
+                    if let Ok(item) = scene_query.get(selected) {
+                        properties(ui, &item, history);
                     } else {
                         ui.label("Selected entity not found");
                     }
@@ -259,14 +262,104 @@ pub fn editor(
                 }
             });
 
+        egui::CollapsingHeader::new("Interactions")
+            .default_open(false)
+            .show(ui, |ui| {
+                egui::Grid::new("interactions_grid")
+                    .num_columns(2)
+                    .spacing([8.0, 4.0])
+                    .show(ui, |ui| {
+                        let coulomb = &mut *settings.coulomb;
+
+                        ui.label("Coulomb:");
+                        ui.checkbox(&mut coulomb.enabled, "Enabled");
+                        ui.end_row();
+
+                        ui.label("Constant:");
+                        ui.add(egui::DragValue::new(&mut coulomb.constant).speed(100.0));
+                        ui.end_row();
+
+                        ui.label("Softening:");
+                        ui.add(
+                            egui::DragValue::new(&mut coulomb.softening)
+                                .speed(0.1)
+                                .range(0.0..=f32::MAX),
+                        );
+                        ui.end_row();
+
+                        ui.label("Cutoff:");
+                        ui.horizontal(|ui| {
+                            let mut limited = coulomb.cutoff.is_some();
+                            if ui.checkbox(&mut limited, "").changed() {
+                                coulomb.cutoff = limited.then_some(1000.0);
+                            }
+                            if let Some(cutoff) = coulomb.cutoff.as_mut() {
+                                ui.add(
+                                    egui::DragValue::new(cutoff)
+                                        .speed(10.0)
+                                        .range(0.0..=f32::MAX),
+                                );
+                            }
+                        });
+                        ui.end_row();
+
+                        let integrator = &mut *settings.integrator;
+
+                        ui.label("Charges:");
+                        ui.horizontal(|ui| {
+                            for method in [ChargeIntegrator::Explicit, ChargeIntegrator::Boris] {
+                                ui.radio_value(integrator, method, method.display_name());
+                            }
+                        });
+                        ui.end_row();
+
+                        let solver = &mut *settings.solver;
+
+                        ui.label("Solver:");
+                        ui.horizontal(|ui| {
+                            for method in [ForceSolver::Exact, ForceSolver::BarnesHut] {
+                                ui.radio_value(&mut solver.solver, method, method.display_name());
+                            }
+                        });
+                        ui.end_row();
+
+                        if solver.solver == ForceSolver::BarnesHut {
+                            ui.label("Theta:");
+                            ui.add(
+                                egui::DragValue::new(&mut solver.theta)
+                                    .speed(0.01)
+                                    .range(0.0..=2.0),
+                            );
+                            ui.end_row();
+
+                            ui.label("Error:");
+                            ui.checkbox(&mut solver.measure_error, "Compare to exact");
+                            ui.end_row();
+
+                            if solver.measure_error {
+                                force_error_row(ui, "Coulomb err:", diagnostics.coulomb);
+                                force_error_row(ui, "Gravity err:", diagnostics.gravity);
+                            }
+                        }
+                    });
+            });
+
         // [todo] 交互逻辑有问题
         egui::CollapsingHeader::new("Outliner")
             .default_open(true)
             .show(ui, |ui| {
-                for (entity, _transform, name, _charge, _velocity, _rigid_body) in
-                    entity_query.iter()
-                {
-                    let is_selected = selection.selected_entity == Some(entity);
+                let mut entities: Vec<_> = entity_query
+                    .iter()
+                    .map(
+                        |(entity, _transform, name, _charge, _velocity, _rigid_body)| {
+                            (entity, name)
+                        },
+                    )
+                    .collect();
+                entities.sort_by_key(|(entity, _)| *entity);
+
+                for (index, &(entity, name)) in entities.iter().enumerate() {
+                    let is_selected = selection.contains(entity);
                     let display_name = name
                         .map(|n| n.0.clone())
                         .unwrap_or_else(|| format!("Entity #{}", entity.index()));
@@ -274,8 +367,37 @@ pub fn editor(
                     let response = ui.selectable_label(is_selected, display_name);
 
                     if response.clicked() {
-                        selection.selected_entity = Some(entity);
+                        let modifiers = ui.input(|input| input.modifiers);
+                        let anchor = selection
+                            .selected_entity
+                            .and_then(|selected| entities.iter().position(|(e, _)| *e == selected));
+                        if modifiers.shift
+                            && let Some(anchor) = anchor
+                        {
+                            let range = anchor.min(index)..=anchor.max(index);
+                            selection.extend(entities[range].iter().map(|(e, _)| *e));
+                        } else if modifiers.command {
+                            selection.toggle(entity);
+                        } else {
+                            selection.select(entity);
+                        }
                     }
+                    response.context_menu(|ui| {
+                        ui.label("Plot");
+                        for quantity in PlotQuantity::SINGLE {
+                            if ui.button(quantity.display_name()).clicked() {
+                                plot.add(entity, quantity);
+                                ui.close();
+                            }
+                        }
+                        if let Some(selected) = selection.selected_entity
+                            && selected != entity
+                            && ui.button("Distance to selected").clicked()
+                        {
+                            plot.add(entity, PlotQuantity::Distance(selected));
+                            ui.close();
+                        }
+                    });
                 }
             });
 
@@ -317,3 +439,455 @@ pub fn editor(
             });
     });
 }
+
+/// Edits go through the history; a drag on one value undoes as a single step.
+fn properties(ui: &mut egui::Ui, item: &SceneEntityQueryItem, history: &mut EditHistory) {
+    let before = item.to_data();
+    let mut after = before.clone();
+    let mut changed = false;
+    let mut merge = false;
+    let mut track = |response: egui::Response| {
+        if response.changed() {
+            changed = true;
+            merge |= response.dragged() && !response.drag_started();
+        }
+    };
+
+    egui::Grid::new("properties_grid")
+        .num_columns(2)
+        .spacing([10.0, 5.0])
+        .show(ui, |ui| {
+            ui.label("Name:");
+            ui.label(&before.name);
+            ui.end_row();
+
+            ui.label("Entity:");
+            ui.label(format!("{}", item.entity.index()));
+            ui.end_row();
+
+            ui.label("Position:");
+            ui.horizontal(|ui| {
+                let translation = &mut after.transform.translation;
+                ui.label("X");
+                track(ui.add(egui::DragValue::new(&mut translation.x).speed(1.0)));
+                ui.label("Y");
+                track(ui.add(egui::DragValue::new(&mut translation.y).speed(1.0)));
+            });
+            ui.end_row();
+
+            ui.label("Rotation:");
+            let mut degrees = after.transform.rotation.to_degrees();
+            let response = ui.add(egui::DragValue::new(&mut degrees).speed(1.0).suffix("°"));
+            if response.changed() {
+                after.transform.rotation = degrees.to_radians();
+            }
+            track(response);
+            ui.end_row();
+
+            if let Some(rigid_body) = after.rigid_body.as_mut() {
+                ui.label("Body:");
+                ui.label(format!("{:?}", rigid_body.body_type));
+                ui.end_row();
+
+                ui.label("Velocity:");
+                ui.horizontal(|ui| {
+                    let velocity = &mut rigid_body.initial_velocity;
+                    ui.label("X");
+                    track(ui.add(egui::DragValue::new(&mut velocity.x).speed(1.0)));
+                    ui.label("Y");
+                    track(ui.add(egui::DragValue::new(&mut velocity.y).speed(1.0)));
+                });
+                ui.end_row();
+
+                if let Some(mass) = rigid_body.mass.as_mut() {
+                    ui.label("Mass:");
+                    track(
+                        ui.add(
+                            egui::DragValue::new(mass)
+                                .speed(0.1)
+                                .range(0.001..=f32::MAX),
+                        ),
+                    );
+                    ui.end_row();
+                }
+            }
+
+            if let Some(charge) = after.charge.as_mut() {
+                ui.label("Charge:");
+                track(ui.add(egui::DragValue::new(charge).speed(1.0)));
+                ui.end_row();
+            }
+
+            if let Some(field) = after.field.as_mut() {
+                ui.label("Strength:");
+                track(ui.add(egui::DragValue::new(&mut field.strength).speed(1.0)));
+                ui.end_row();
+
+                field_profile_rows(ui, "selected_field_profile", &mut field.profile);
+                waveform_rows(
+                    ui,
+                    "Strength(t):",
+                    "selected_strength_wave",
+                    &mut field.modulation.strength,
+                    1.0,
+                );
+                waveform_rows(
+                    ui,
+                    "Rotation(t):",
+                    "selected_rotation_wave",
+                    &mut field.modulation.rotation,
+                    0.0,
+                );
+            }
+        });
+
+    // The profile and waveform rows edit in place rather than through `track`.
+    if let (Some(before_field), Some(after_field)) = (&before.field, &after.field)
+        && (before_field.profile != after_field.profile
+            || before_field.modulation != after_field.modulation)
+    {
+        changed = true;
+        merge |= ui.input(|input| input.pointer.any_down() && !input.pointer.any_pressed());
+    }
+
+    if changed {
+        history.execute(
+            "Edit Properties",
+            EditCommand::Modify {
+                entity: item.entity,
+                before,
+                after,
+            },
+            merge,
+        );
+    }
+}
+
+/// Values shared by a multi-selection. Each is shown as the first entity has
+/// it, and an edit sets it on every selected entity that carries it.
+fn group_properties(ui: &mut egui::Ui, items: &[SceneEntityQueryItem], history: &mut EditHistory) {
+    let befores: Vec<_> = items
+        .iter()
+        .map(|item| (item.entity, item.to_data()))
+        .collect();
+    let mut afters: Vec<_> = befores.iter().map(|(_, data)| data.clone()).collect();
+
+    let mut charge = befores.iter().find_map(|(_, data)| data.charge);
+    let mut mass = befores
+        .iter()
+        .find_map(|(_, data)| data.rigid_body.as_ref()?.mass);
+    let mut velocity = befores
+        .iter()
+        .find_map(|(_, data)| Some(data.rigid_body.as_ref()?.initial_velocity));
+    let mut strength = befores
+        .iter()
+        .find_map(|(_, data)| Some(data.field.as_ref()?.strength));
+
+    let mut changed = false;
+    let mut merge = false;
+    let mut track = |response: egui::Response| {
+        if response.changed() {
+            changed = true;
+            merge |= response.dragged() && !response.drag_started();
+        }
+        response.changed()
+    };
+
+    egui::Grid::new("group_properties_grid")
+        .num_columns(2)
+        .spacing([10.0, 5.0])
+        .show(ui, |ui| {
+            ui.label("Selected:");
+            ui.label(format!("{} entities", items.len()));
+            ui.end_row();
+
+            if let Some(velocity) = velocity.as_mut() {
+                ui.label("Velocity:");
+                let edited = ui
+                    .horizontal(|ui| {
+                        ui.label("X");
+                        let x = track(ui.add(egui::DragValue::new(&mut velocity.x).speed(1.0)));
+                        ui.label("Y");
+                        let y = track(ui.add(egui::DragValue::new(&mut velocity.y).speed(1.0)));
+                        x || y
+                    })
+                    .inner;
+                if edited {
+                    for rigid_body in afters
+                        .iter_mut()
+                        .filter_map(|data| data.rigid_body.as_mut())
+                    {
+                        rigid_body.initial_velocity = *velocity;
+                    }
+                }
+                ui.end_row();
+            }
+
+            if let Some(mass) = mass.as_mut() {
+                ui.label("Mass:");
+                if track(
+                    ui.add(
+                        egui::DragValue::new(mass)
+                            .speed(0.1)
+                            .range(0.001..=f32::MAX),
+                    ),
+                ) {
+                    for rigid_body in afters
+                        .iter_mut()
+                        .filter_map(|data| data.rigid_body.as_mut())
+                    {
+                        if rigid_body.mass.is_some() {
+                            rigid_body.mass = Some(*mass);
+                        }
+                    }
+                }
+                ui.end_row();
+            }
+
+            if let Some(charge) = charge.as_mut() {
+                ui.label("Charge:");
+                if track(ui.add(egui::DragValue::new(charge).speed(1.0))) {
+                    for value in afters.iter_mut().filter_map(|data| data.charge.as_mut()) {
+                        *value = *charge;
+                    }
+                }
+                ui.end_row();
+            }
+
+            if let Some(strength) = strength.as_mut() {
+                ui.label("Strength:");
+                if track(ui.add(egui::DragValue::new(strength).speed(1.0))) {
+                    for field in afters.iter_mut().filter_map(|data| data.field.as_mut()) {
+                        field.strength = *strength;
+                    }
+                }
+                ui.end_row();
+            }
+        });
+
+    if changed {
+        let commands = befores
+            .into_iter()
+            .zip(afters)
+            .map(|((entity, before), after)| EditCommand::Modify {
+                entity,
+                before,
+                after,
+            })
+            .collect();
+        history.execute("Edit Properties", EditCommand::group(commands), merge);
+    }
+}
+
+fn force_error_row(ui: &mut egui::Ui, label: &str, error: Option<ForceError>) {
+    ui.label(label);
+    match error {
+        Some(error) => ui.label(format!(
+            "mean {:.3}% / max {:.3}% ({} bodies)",
+            error.mean_relative * 100.0,
+            error.max_relative * 100.0,
+            error.samples
+        )),
+        None => ui.label("-"),
+    };
+    ui.end_row();
+}
+
+fn field_profile_rows(ui: &mut egui::Ui, id: &str, profile: &mut FieldProfile) {
+    ui.label("Profile:");
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(profile.display_name())
+        .show_ui(ui, |ui| {
+            let options = [
+                FieldProfile::Uniform,
+                FieldProfile::Radial { softening: 5.0 },
+                FieldProfile::Wire { softening: 5.0 },
+                FieldProfile::Gradient {
+                    gradient: Vec2::ZERO,
+                },
+            ];
+            for option in options {
+                let selected = std::mem::discriminant(profile) == std::mem::discriminant(&option);
+                if ui
+                    .selectable_label(selected, option.display_name())
+                    .clicked()
+                    && !selected
+                {
+                    *profile = option;
+                }
+            }
+            let is_grid = matches!(profile, FieldProfile::Grid(_));
+            if ui.selectable_label(is_grid, "Grid...").clicked() {
+                if let Some(path) = rfd::FileDialog::new()
+                    .add_filter("CSV", &["csv"])
+                    .pick_file()
+                {
+                    match FieldGrid::load_csv(&path) {
+                        Ok(grid) => *profile = FieldProfile::Grid(grid),
+                        Err(e) => bevy::log::error!("Failed to load field grid: {}", e),
+                    }
+                }
+            }
+        });
+    ui.end_row();
+
+    match profile {
+        FieldProfile::Uniform => {}
+        FieldProfile::Radial { softening } | FieldProfile::Wire { softening } => {
+            ui.label("Softening:");
+            ui.add(
+                egui::DragValue::new(softening)
+                    .speed(0.1)
+                    .range(0.0..=f32::MAX),
+            );
+            ui.end_row();
+        }
+        FieldProfile::Gradient { gradient } => {
+            ui.label("Gradient:");
+            ui.horizontal(|ui| {
+                ui.label("X");
+                ui.add(egui::DragValue::new(&mut gradient.x).speed(0.01));
+                ui.label("Y");
+                ui.add(egui::DragValue::new(&mut gradient.y).speed(0.01));
+            });
+            ui.end_row();
+        }
+        FieldProfile::Grid(grid) => {
+            ui.label("Grid:");
+            ui.label(format!(
+                "{}x{} from {}",
+                grid.columns,
+                grid.rows,
+                grid.source.as_deref().unwrap_or("scene")
+            ));
+            ui.end_row();
+        }
+    }
+}
+
+/// `neutral` is the value a freshly selected waveform starts around.
+fn waveform_rows(ui: &mut egui::Ui, label: &str, id: &str, wave: &mut Waveform, neutral: f32) {
+    ui.label(label);
+    egui::ComboBox::from_id_salt(id)
+        .selected_text(wave.display_name())
+        .show_ui(ui, |ui| {
+            let options = [
+                Waveform::Constant(neutral),
+                Waveform::Sine {
+                    amplitude: 1.0,
+                    frequency: 1.0,
+                    phase: 0.0,
+                    offset: neutral,
+                },
+                Waveform::Pulse {
+                    high: 1.0,
+                    low: 0.0,
+                    period: 1.0,
+                    duty: 0.5,
+                    delay: 0.0,
+                },
+                Waveform::Ramp {
+                    from: 0.0,
+                    to: 1.0,
+                    start: 0.0,
+                    duration: 1.0,
+                },
+                Waveform::Table(vec![(0.0, neutral), (1.0, neutral)]),
+            ];
+            for option in options {
+                let selected = std::mem::discriminant(wave) == std::mem::discriminant(&option);
+                if ui
+                    .selectable_label(selected, option.display_name())
+                    .clicked()
+                    && !selected
+                {
+                    *wave = option;
+                }
+            }
+        });
+    ui.end_row();
+
+    fn param(ui: &mut egui::Ui, name: &str, value: &mut f32, speed: f32) {
+        ui.label(name);
+        ui.add(egui::DragValue::new(value).speed(speed));
+    }
+
+    match wave {
+        Waveform::Constant(value) => {
+            ui.label("");
+            ui.horizontal(|ui| param(ui, "Value", value, 0.1));
+            ui.end_row();
+        }
+        Waveform::Sine {
+            amplitude,
+            frequency,
+            phase,
+            offset,
+        } => {
+            ui.label("");
+            ui.horizontal_wrapped(|ui| {
+                param(ui, "Amp", amplitude, 0.1);
+                param(ui, "Hz", frequency, 0.01);
+                param(ui, "Phase", phase, 0.01);
+                param(ui, "Offset", offset, 0.1);
+            });
+            ui.end_row();
+        }
+        Waveform::Pulse {
+            high,
+            low,
+            period,
+            duty,
+            delay,
+        } => {
+            ui.label("");
+            ui.horizontal_wrapped(|ui| {
+                param(ui, "High", high, 0.1);
+                param(ui, "Low", low, 0.1);
+                param(ui, "Period", period, 0.01);
+                param(ui, "Duty", duty, 0.01);
+                param(ui, "Delay", delay, 0.01);
+            });
+            ui.end_row();
+        }
+        Waveform::Ramp {
+            from,
+            to,
+            start,
+            duration,
+        } => {
+            ui.label("");
+            ui.horizontal_wrapped(|ui| {
+                param(ui, "From", from, 0.1);
+                param(ui, "To", to, 0.1);
+                param(ui, "Start", start, 0.01);
+                param(ui, "Duration", duration, 0.01);
+            });
+            ui.end_row();
+        }
+        Waveform::Table(points) => {
+            let mut remove = None;
+            for (index, (time, value)) in points.iter_mut().enumerate() {
+                ui.label("");
+                ui.horizontal(|ui| {
+                    param(ui, "t", time, 0.01);
+                    param(ui, "v", value, 0.1);
+                    if ui.small_button("x").clicked() {
+                        remove = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+            if let Some(index) = remove {
+                points.remove(index);
+            }
+            ui.label("");
+            if ui.small_button("+ Point").clicked() {
+                let next = points.last().map_or((0.0, 0.0), |(t, v)| (t + 1.0, *v));
+                points.push(next);
+            }
+            ui.end_row();
+            points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        }
+    }
+}
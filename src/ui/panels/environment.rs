@@ -0,0 +1,240 @@
+use bevy_egui::egui;
+
+use crate::simulation::{
+    ConservationDiagnostics, ConservationSettings, GravityMode, SimulationSettings,
+};
+
+pub fn environment(
+    ui: &mut egui::Ui,
+    settings: &mut SimulationSettings,
+    conservation: &mut ConservationDiagnostics,
+) {
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label("Environment");
+        ui.separator();
+
+        egui::Grid::new("environment_grid")
+            .num_columns(2)
+            .spacing([8.0, 4.0])
+            .show(ui, |ui| {
+                let gravity = &mut *settings.gravity;
+
+                ui.label("Gravity:");
+                ui.horizontal(|ui| {
+                    for mode in [GravityMode::Uniform, GravityMode::Mutual, GravityMode::None] {
+                        ui.radio_value(&mut gravity.mode, mode, mode.display_name());
+                    }
+                });
+                ui.end_row();
+
+                match gravity.mode {
+                    GravityMode::Uniform => {
+                        ui.label("Accel:");
+                        ui.horizontal(|ui| {
+                            ui.label("X");
+                            ui.add(egui::DragValue::new(&mut gravity.uniform.x).speed(1.0));
+                            ui.label("Y");
+                            ui.add(egui::DragValue::new(&mut gravity.uniform.y).speed(1.0));
+                        });
+                        ui.end_row();
+                    }
+                    GravityMode::Mutual => {
+                        ui.label("G:");
+                        ui.add(egui::DragValue::new(&mut gravity.constant).speed(10.0));
+                        ui.end_row();
+
+                        ui.label("Softening:");
+                        ui.add(
+                            egui::DragValue::new(&mut gravity.softening)
+                                .speed(0.1)
+                                .range(0.0..=f32::MAX),
+                        );
+                        ui.end_row();
+                    }
+                    GravityMode::None => {}
+                }
+
+                let physics = &mut *settings.physics;
+
+                ui.label("Lin. damping:");
+                ui.add(
+                    egui::DragValue::new(&mut physics.linear_damping)
+                        .speed(0.01)
+                        .range(0.0..=f32::MAX),
+                );
+                ui.end_row();
+
+                ui.label("Ang. damping:");
+                ui.add(
+                    egui::DragValue::new(&mut physics.angular_damping)
+                        .speed(0.01)
+                        .range(0.0..=f32::MAX),
+                );
+                ui.end_row();
+
+                ui.label("Substeps:");
+                ui.add(egui::DragValue::new(&mut physics.substeps).range(1..=64));
+                ui.end_row();
+
+                ui.label("Timestep:");
+                ui.add(
+                    egui::DragValue::new(&mut physics.timestep_hz)
+                        .speed(1.0)
+                        .range(1.0..=1000.0)
+                        .suffix(" Hz"),
+                );
+                ui.end_row();
+
+                ui.label("Length unit:");
+                ui.add(
+                    egui::DragValue::new(&mut physics.length_unit)
+                        .speed(0.1)
+                        .range(0.01..=f32::MAX),
+                );
+                ui.end_row();
+
+                ui.label("Deterministic:");
+                ui.checkbox(&mut physics.deterministic, "Fixed step");
+                ui.end_row();
+            });
+
+        egui::CollapsingHeader::new("Conservation")
+            .default_open(true)
+            .show(ui, |ui| {
+                conservation_rows(ui, &mut settings.conservation, conservation)
+            });
+    });
+}
+
+fn conservation_rows(
+    ui: &mut egui::Ui,
+    settings: &mut ConservationSettings,
+    conservation: &mut ConservationDiagnostics,
+) {
+    egui::Grid::new("conservation_settings_grid")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Measure:");
+            if ui
+                .checkbox(&mut settings.enabled, "Energy and momentum")
+                .changed()
+            {
+                // The old baseline no longer matches the steps that were skipped.
+                conservation.reset_baseline();
+            }
+            ui.end_row();
+
+            ui.label("Every:");
+            ui.add(
+                egui::DragValue::new(&mut settings.sample_every)
+                    .range(1..=u32::MAX)
+                    .suffix(" steps"),
+            );
+            ui.end_row();
+
+            ui.label("Tolerance:");
+            let mut percent = settings.tolerance * 100.0;
+            if ui
+                .add(
+                    egui::DragValue::new(&mut percent)
+                        .speed(0.1)
+                        .range(0.0..=100.0)
+                        .suffix(" %"),
+                )
+                .changed()
+            {
+                settings.tolerance = percent / 100.0;
+            }
+            ui.end_row();
+        });
+
+    if !settings.enabled {
+        return;
+    }
+    let Some(current) = conservation.current else {
+        ui.weak("Measured while the simulation runs.");
+        return;
+    };
+    let tolerance = settings.tolerance;
+
+    egui::Grid::new("conservation_grid")
+        .num_columns(2)
+        .spacing([8.0, 4.0])
+        .show(ui, |ui| {
+            ui.label("Kinetic:");
+            ui.label(format!("{:.4e}", current.kinetic_energy));
+            ui.end_row();
+
+            ui.label("Gravitational:");
+            ui.label(format!("{:.4e}", current.gravitational_energy));
+            ui.end_row();
+
+            ui.label("Electric:");
+            ui.label(format!("{:.4e}", current.electric_energy));
+            ui.end_row();
+
+            ui.label("Total energy:");
+            ui.label(format!("{:.4e}", current.total_energy()));
+            ui.end_row();
+
+            ui.label("Momentum:");
+            ui.label(format!(
+                "({:.3e}, {:.3e})",
+                current.linear_momentum.x, current.linear_momentum.y
+            ));
+            ui.end_row();
+
+            ui.label("Ang. momentum:");
+            ui.label(format!("{:.4e}", current.angular_momentum));
+            ui.end_row();
+
+            ui.separator();
+            ui.end_row();
+
+            drift_row(
+                ui,
+                "Energy drift:",
+                conservation.energy_drift,
+                conservation.energy_flagged(tolerance),
+            );
+            if conservation.momentum_conserved {
+                drift_row(
+                    ui,
+                    "Momentum drift:",
+                    conservation.momentum_drift,
+                    conservation.momentum_flagged(tolerance),
+                );
+                drift_row(
+                    ui,
+                    "Ang. mom. drift:",
+                    conservation.angular_momentum_drift,
+                    conservation.angular_momentum_flagged(tolerance),
+                );
+            } else {
+                ui.label("Momentum drift:");
+                ui.weak("n/a")
+                    .on_hover_text("External forces or static bodies act on the scene");
+                ui.end_row();
+            }
+        });
+
+    if ui
+        .button("Reset baseline")
+        .on_hover_text("Measure drift from the next step on")
+        .clicked()
+    {
+        conservation.reset_baseline();
+    }
+}
+
+fn drift_row(ui: &mut egui::Ui, label: &str, drift: f32, flagged: bool) {
+    ui.label(label);
+    let text = format!("{:.3} %", drift * 100.0);
+    if flagged {
+        ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {}", text));
+    } else {
+        ui.label(text);
+    }
+    ui.end_row();
+}
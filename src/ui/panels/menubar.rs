@@ -1,15 +1,22 @@
-use avian2d::prelude::{Collider, LinearVelocity, RigidBody};
+use bevy::ecs::entity::EntityHashMap;
 use bevy::prelude::*;
 use bevy_egui::egui;
 use rfd::FileDialog;
 
 use crate::{
+    app::Recording,
     camera::components::WorldCamera,
-    editor::{EntityName, PlacementState, components::EditorEntity, resources::SelectionState},
-    project::{document::Document, export, import},
+    editor::{
+        EditCommand, EditHistory, PlacementState, components::EditorEntity,
+        resources::SelectionState,
+    },
+    project::file_format::EnvironmentData,
+    project::{
+        SceneEntityQuery, TrajectoryData, collect_scene_data, document::Document, export,
+        export_trajectories, import,
+    },
     settings::editor_prefs::EditorPrefs,
-    shared::{EntityShape, InitialState},
-    simulation::components::{Charge, Electric, Field, Magnetic},
+    simulation::SimulationSettings,
     ui::UiPanelVisibility,
 };
 
@@ -23,9 +30,11 @@ pub fn menubar(
     mut camera: Single<&mut Transform, With<WorldCamera>>,
     editor_prefs: &mut EditorPrefs,
     document: &mut ResMut<Document>,
+    history: &mut EditHistory,
     selection: &mut ResMut<SelectionState>,
     placement: &mut ResMut<PlacementState>,
-    placed_entities: &mut ResMut<crate::editor::resources::PlacedEntities>,
+    settings: &mut SimulationSettings,
+    recording: &Recording,
     // entity_query: Query<
     //     (
     //         Entity,
@@ -37,34 +46,18 @@ pub fn menubar(
     //     ),
     //     (With<EditorEntity>, Without<WorldCamera>),
     // >,
-    save_entity_query: Query<
-        (
-            Entity,
-            &Transform,
-            Option<&crate::editor::EntityName>,
-            Option<&Charge>,
-            Option<&LinearVelocity>,
-            Option<&RigidBody>,
-            Option<&Magnetic>,
-            Option<&Electric>,
-            Option<&Field>,
-        ),
-        (With<EditorEntity>, Without<WorldCamera>),
-    >,
-    commands: &mut Commands,
+    save_entity_query: &Query<SceneEntityQuery, (With<EditorEntity>, Without<WorldCamera>)>,
 ) {
     egui::MenuBar::new().ui(ui, |ui| {
         ui.menu_button("File", |ui| {
             if ui.button("New").clicked() {
-                for entity in placed_entities.entities.iter() {
-                    commands.entity(*entity).despawn();
-                }
-                placed_entities.entities.clear();
-                selection.selected_entity = None;
                 **placement = PlacementState::default();
+                EnvironmentData::default().apply(settings);
+                document.saved_environment = EnvironmentData::default();
                 document.path = None;
                 document.is_dirty = false;
                 document.data = crate::project::file_format::SceneData::default();
+                document.needs_rebuild = true;
             }
             if ui.button("Open").clicked() {
                 if let Some(path) = FileDialog::new()
@@ -74,9 +67,12 @@ pub fn menubar(
                 {
                     match import::import_scene(&path) {
                         Ok(data) => {
+                            data.environment.apply(settings);
+                            document.saved_environment = data.environment.clone();
                             document.data = data;
                             document.path = Some(path);
                             document.is_dirty = false;
+                            document.needs_rebuild = true;
                         }
                         Err(e) => {
                             bevy::log::error!("Failed to open file: {}", e);
@@ -86,9 +82,11 @@ pub fn menubar(
             }
             if ui.button("Save").clicked() {
                 if let Some(ref path) = document.path {
-                    let data = collect_scene_data(&save_entity_query);
+                    let data = collect_scene_data(save_entity_query, settings);
                     match export::export_scene(path, &data) {
                         Ok(_) => {
+                            history.mark_saved();
+                            document.saved_environment = data.environment;
                             document.is_dirty = false;
                         }
                         Err(e) => {
@@ -101,9 +99,11 @@ pub fn menubar(
                         .add_filter("RON", &["ron"])
                         .save_file()
                     {
-                        let data = collect_scene_data(&save_entity_query);
+                        let data = collect_scene_data(save_entity_query, settings);
                         match export::export_scene(&path, &data) {
                             Ok(_) => {
+                                history.mark_saved();
+                                document.saved_environment = data.environment;
                                 document.path = Some(path);
                                 document.is_dirty = false;
                             }
@@ -120,9 +120,11 @@ pub fn menubar(
                     .add_filter("RON", &["ron"])
                     .save_file()
                 {
-                    let data = collect_scene_data(&save_entity_query);
+                    let data = collect_scene_data(save_entity_query, settings);
                     match export::export_scene(&path, &data) {
                         Ok(_) => {
+                            history.mark_saved();
+                            document.saved_environment = data.environment;
                             document.path = Some(path);
                             document.is_dirty = false;
                         }
@@ -133,64 +135,116 @@ pub fn menubar(
                 }
             }
             ui.separator();
+            ui.menu_button("Export Trajectories", |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Interval:");
+                    ui.add(
+                        egui::DragValue::new(&mut editor_prefs.trajectory_interval)
+                            .speed(0.01)
+                            .range(0.0..=60.0)
+                            .suffix(" s"),
+                    )
+                    .on_hover_text("0 exports every recorded step");
+                });
+                let branch = recording.active_branch();
+                ui.weak(format!("Exports the shown run, {}.", branch.name));
+                if branch.wrapped
+                    && let (Some(first), Some(last)) = (branch.frames.front(), branch.frames.back())
+                {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!(
+                            "⚠ The recording wrapped; only {:.1}–{:.1} s are kept.",
+                            first.time, last.time
+                        ),
+                    );
+                }
+                let has_frames = !recording.frames().is_empty();
+                if ui
+                    .add_enabled(has_frames, egui::Button::new("Export..."))
+                    .clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("CSV", &["csv"])
+                        .add_filter("JSON", &["json"])
+                        .save_file()
+                {
+                    // Entities placed during the run aren't in the branch yet.
+                    let mut names: EntityHashMap<String> = save_entity_query
+                        .iter()
+                        .map(|item| {
+                            let name = item.name.map(|n| n.0.clone()).unwrap_or_default();
+                            (item.entity, name)
+                        })
+                        .collect();
+                    names.extend(recording.entity_names());
+                    let data = TrajectoryData::from_frames(
+                        recording.frames(),
+                        &names,
+                        editor_prefs.trajectory_interval,
+                    );
+                    if let Err(e) = export_trajectories(&path, &data) {
+                        bevy::log::error!("Failed to export trajectories: {}", e);
+                    }
+                }
+            });
+            ui.separator();
             if ui.button("Exit").clicked() {
                 std::process::exit(0);
             }
         });
         ui.menu_button("Edit", |ui| {
-            let can_edit = selection.selected_entity.is_some();
+            let undo_label = history.undo_label();
+            let undo = egui::Button::new(match undo_label {
+                Some(label) => format!("Undo {}", label),
+                None => "Undo".to_string(),
+            })
+            .shortcut_text("Ctrl+Z");
+            if ui.add_enabled(undo_label.is_some(), undo).clicked() {
+                history.undo();
+            }
+            let redo_label = history.redo_label();
+            let redo = egui::Button::new(match redo_label {
+                Some(label) => format!("Redo {}", label),
+                None => "Redo".to_string(),
+            })
+            .shortcut_text("Ctrl+Shift+Z");
+            if ui.add_enabled(redo_label.is_some(), redo).clicked() {
+                history.redo();
+            }
+            ui.separator();
+
+            let can_edit = !selection.is_empty();
             ui.add_enabled_ui(can_edit, |ui| {
                 if ui.button("Delete").clicked() {
-                    if let Some(entity) = selection.selected_entity {
-                        commands.entity(entity).despawn();
-                        placed_entities.entities.retain(|e| *e != entity);
-                        selection.selected_entity = None;
-                        document.is_dirty = true;
+                    let despawns: Vec<_> = save_entity_query
+                        .iter_many(selection.entities.iter().copied())
+                        .map(|item| EditCommand::Despawn {
+                            entity: item.entity,
+                            data: item.to_data(),
+                        })
+                        .collect();
+                    if !despawns.is_empty() {
+                        history.execute("Delete", EditCommand::group(despawns), false);
                     }
                 }
                 if ui.button("Duplicate").clicked() {
-                    if let Some(entity) = selection.selected_entity {
-                        if let Ok((
-                            _,
-                            transform,
-                            name,
-                            _charge,
-                            _velocity,
-                            _rigid_body,
-                            _magnetic,
-                            _electric,
-                            _field,
-                        )) = save_entity_query.get(entity)
-                        {
-                            let new_name = name
+                    let spawns: Vec<_> = save_entity_query
+                        .iter_many(selection.entities.iter().copied())
+                        .map(|item| {
+                            let mut data = item.to_data();
+                            data.name = item
+                                .name
                                 .map(|n| format!("{} Copy", n.0))
                                 .unwrap_or_else(|| "Entity Copy".to_string());
-
-                            let offset = Vec3::new(50.0, 50.0, 0.0);
-                            let mut new_transform =
-                                Transform::from_translation(transform.translation + offset);
-                            new_transform.scale = transform.scale;
-
-                            let new_entity = commands
-                                .spawn((
-                                    EditorEntity,
-                                    EntityName(new_name),
-                                    new_transform,
-                                    EntityShape::Circle,
-                                    RigidBody::Dynamic,
-                                    Collider::circle(25.0),
-                                    LinearVelocity::default(),
-                                    InitialState {
-                                        transform: new_transform,
-                                        velocity: Vec2::ZERO,
-                                    },
-                                ))
-                                .id();
-
-                            placed_entities.entities.push(new_entity);
-                            selection.selected_entity = Some(new_entity);
-                            document.is_dirty = true;
-                        }
+                            data.transform.translation += Vec3::new(50.0, 50.0, 0.0);
+                            EditCommand::Spawn {
+                                entity: Entity::PLACEHOLDER,
+                                data,
+                            }
+                        })
+                        .collect();
+                    if !spawns.is_empty() {
+                        history.execute("Duplicate", EditCommand::group(spawns), false);
                     }
                 }
             });
@@ -206,9 +260,21 @@ pub fn menubar(
             if ui.button("Toggle Debug View").clicked() {
                 editor_prefs.show_field_vectors = !editor_prefs.show_field_vectors;
             }
+            ui.checkbox(&mut editor_prefs.snap_to_grid, "Snap to Grid");
+            ui.horizontal(|ui| {
+                ui.label("Grid Size");
+                ui.add(
+                    egui::DragValue::new(&mut editor_prefs.grid_size)
+                        .speed(1.0)
+                        .range(1.0..=1000.0),
+                );
+            });
             ui.separator();
             ui.checkbox(&mut visibility.toolbar, "Toolbar");
             ui.checkbox(&mut visibility.editor, "Editor");
+            ui.checkbox(&mut visibility.environment, "Environment");
+            ui.checkbox(&mut visibility.timeline, "Timeline");
+            ui.checkbox(&mut visibility.plot, "Plot");
             ui.checkbox(&mut visibility.statusbar, "Status Bar");
         });
         ui.menu_button("Help", |ui| {
@@ -255,72 +321,10 @@ pub fn menubar(
                 ui.label("Right Click - Place Entity");
                 ui.label("Scroll - Zoom");
                 ui.label("Middle Drag - Pan");
+                ui.label("Hold Alt - Place and move without snapping");
                 if ui.button("Close").clicked() {
                     SHOW_DOCS.store(false, std::sync::atomic::Ordering::SeqCst);
                 }
             });
     }
 }
-
-fn collect_scene_data(
-    query: &Query<
-        (
-            Entity,
-            &Transform,
-            Option<&crate::editor::EntityName>,
-            Option<&Charge>,
-            Option<&LinearVelocity>,
-            Option<&RigidBody>,
-            Option<&Magnetic>,
-            Option<&Electric>,
-            Option<&Field>,
-        ),
-        (With<EditorEntity>, Without<WorldCamera>),
-    >,
-) -> crate::project::file_format::SceneData {
-    use crate::project::file_format::*;
-
-    let mut entities = Vec::new();
-
-    for (_entity, transform, name, charge, velocity, rigid_body, magnetic, electric, field) in
-        query.iter()
-    {
-        let rigid_body_data = rigid_body.map(|rb| RigidBodyData {
-            body_type: format!("{:?}", rb),
-        });
-
-        let collider_data = None;
-
-        let charge_data = charge.map(|c| c.value);
-
-        let field_data = field.map(|f| {
-            let field_type = if magnetic.is_some() {
-                "magnetic".to_string()
-            } else if electric.is_some() {
-                "electric".to_string()
-            } else {
-                "unknown".to_string()
-            };
-            FieldData {
-                field_type,
-                strength: f.strength,
-                direction: Vec2::new(f.direction.x, f.direction.y),
-            }
-        });
-
-        entities.push(SceneEntityData {
-            name: name.map(|n| n.0.clone()).unwrap_or_default(),
-            transform: TransformData {
-                translation: transform.translation,
-                rotation: 0.0,
-                scale: transform.scale,
-            },
-            rigid_body: rigid_body_data,
-            collider: collider_data,
-            charge: charge_data,
-            field: field_data,
-        });
-    }
-
-    SceneData { entities }
-}
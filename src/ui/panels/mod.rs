@@ -1,11 +1,15 @@
 pub mod editor;
+pub mod environment;
 pub mod menubar;
+pub mod plot;
 pub mod status;
 pub mod timeline;
 pub mod toolbar;
 
 pub use editor::editor;
+pub use environment::environment;
 pub use menubar::menubar;
+pub use plot::plot;
 pub use status::statusbar;
 pub use timeline::timeline;
 pub use toolbar::toolbar;
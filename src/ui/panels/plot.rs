@@ -0,0 +1,232 @@
+use bevy::ecs::entity::EntityHashMap;
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::app::{Frame, Playback, Recording};
+use crate::ui::resources::{PlotQuantity, PlotState, PlotTrace};
+
+pub fn plot(
+    ui: &mut egui::Ui,
+    plot: &mut PlotState,
+    recording: &Recording,
+    playback: &Playback,
+    names: &EntityHashMap<String>,
+    selected: Option<Entity>,
+) {
+    ui.label("Plot");
+    ui.separator();
+
+    if let Some(entity) = selected {
+        ui.horizontal_wrapped(|ui| {
+            ui.label(format!("Add {}:", entity_name(names, entity)));
+            egui::ComboBox::from_id_salt("plot_add_quantity")
+                .selected_text("Quantity")
+                .show_ui(ui, |ui| {
+                    for quantity in PlotQuantity::SINGLE {
+                        if ui
+                            .selectable_label(false, quantity.display_name())
+                            .clicked()
+                        {
+                            plot.add(entity, quantity);
+                        }
+                    }
+                });
+            egui::ComboBox::from_id_salt("plot_add_distance")
+                .selected_text("Distance to")
+                .show_ui(ui, |ui| {
+                    let mut others: Vec<_> = names.iter().filter(|(e, _)| **e != entity).collect();
+                    others.sort_by_key(|(other, _)| **other);
+                    for (other, name) in others {
+                        if ui.selectable_label(false, name).clicked() {
+                            plot.add(entity, PlotQuantity::Distance(*other));
+                        }
+                    }
+                });
+        });
+    } else {
+        ui.weak("Select an entity to plot its quantities.");
+    }
+
+    let mut removed = None;
+    for (index, trace) in plot.traces.iter().enumerate() {
+        ui.horizontal(|ui| {
+            let (swatch, _) = ui.allocate_exact_size(egui::vec2(12.0, 12.0), egui::Sense::hover());
+            ui.painter().rect_filled(swatch, 2.0, trace.color);
+            ui.label(trace_label(trace, names));
+            if ui.small_button("✕").on_hover_text("Remove").clicked() {
+                removed = Some(index);
+            }
+        });
+    }
+    if let Some(index) = removed {
+        plot.traces.remove(index);
+    }
+
+    ui.separator();
+    chart(ui, &plot.traces, recording, playback, names);
+}
+
+/// Draws every trace over the recorded time of the active branch. Nothing is
+/// recorded while paused, so the chart holds still until the run resumes.
+fn chart(
+    ui: &mut egui::Ui,
+    traces: &[PlotTrace],
+    recording: &Recording,
+    playback: &Playback,
+    names: &EntityHashMap<String>,
+) {
+    let size = egui::vec2(ui.available_width(), ui.available_height().max(120.0));
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+    let frames = recording.frames();
+    let (Some(first), Some(last)) = (frames.front(), frames.back()) else {
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Run the simulation to record data",
+            egui::FontId::proportional(12.0),
+            visuals.weak_text_color(),
+        );
+        return;
+    };
+
+    // At most one point per horizontal pixel.
+    let stride = (frames.len() as f32 / rect.width().max(1.0))
+        .ceil()
+        .max(1.0) as usize;
+    let series: Vec<Vec<(f32, f32)>> = traces
+        .iter()
+        .map(|trace| {
+            frames
+                .iter()
+                .step_by(stride)
+                .chain(std::iter::once(last))
+                .filter_map(|frame| Some((frame.time, sample(trace, frame)?)))
+                .collect()
+        })
+        .collect();
+
+    let (mut min, mut max) = (f32::INFINITY, f32::NEG_INFINITY);
+    for (_, value) in series.iter().flatten() {
+        min = min.min(*value);
+        max = max.max(*value);
+    }
+    if !min.is_finite() || !max.is_finite() {
+        (min, max) = (-1.0, 1.0);
+    }
+    if max - min < f32::EPSILON {
+        (min, max) = (min - 1.0, max + 1.0);
+    }
+    let margin = (max - min) * 0.05;
+    let (min, max) = (min - margin, max + margin);
+    let (start, end) = (first.time, last.time.max(first.time + f32::EPSILON));
+
+    let to_screen = |time: f32, value: f32| {
+        egui::pos2(
+            egui::remap(time, start..=end, rect.x_range()),
+            egui::remap(value, max..=min, rect.y_range()),
+        )
+    };
+
+    let axis = egui::Stroke::new(1.0, visuals.weak_text_color());
+    if min < 0.0 && max > 0.0 {
+        let y = to_screen(start, 0.0).y;
+        painter.hline(rect.x_range(), y, axis);
+    }
+
+    for (trace, points) in traces.iter().zip(&series) {
+        let points: Vec<_> = points
+            .iter()
+            .map(|(time, value)| to_screen(*time, *value))
+            .collect();
+        painter.add(egui::Shape::line(
+            points,
+            egui::Stroke::new(1.5, trace.color),
+        ));
+    }
+
+    if playback.cursor.is_some() {
+        let x = to_screen(playback.time, 0.0).x;
+        painter.vline(
+            x,
+            rect.y_range(),
+            egui::Stroke::new(1.0, visuals.strong_text_color()),
+        );
+    }
+
+    let font = egui::FontId::monospace(10.0);
+    let text_color = visuals.text_color();
+    painter.text(
+        rect.left_top() + egui::vec2(4.0, 2.0),
+        egui::Align2::LEFT_TOP,
+        format!("{:.3}", max),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        rect.left_bottom() + egui::vec2(4.0, -2.0),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{:.3}", min),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        rect.right_bottom() + egui::vec2(-4.0, -2.0),
+        egui::Align2::RIGHT_BOTTOM,
+        format!("{:.2} – {:.2} s", start, end),
+        font,
+        text_color,
+    );
+
+    if let Some(pointer) = response.hover_pos() {
+        let time = egui::remap(pointer.x, rect.x_range(), start..=end);
+        let frame = &frames[recording.index_at(time)];
+        painter.vline(pointer.x, rect.y_range(), axis);
+        response.on_hover_ui_at_pointer(|ui| {
+            ui.label(format!("t = {:.3} s", frame.time));
+            for trace in traces {
+                if let Some(value) = sample(trace, frame) {
+                    ui.colored_label(
+                        trace.color,
+                        format!("{}: {:.3}", trace_label(trace, names), value),
+                    );
+                }
+            }
+        });
+    }
+}
+
+fn sample(trace: &PlotTrace, frame: &Frame) -> Option<f32> {
+    let body = frame.bodies.get(&trace.entity)?;
+    Some(match trace.quantity {
+        PlotQuantity::X => body.position.x,
+        PlotQuantity::Y => body.position.y,
+        PlotQuantity::Speed => body.linear_velocity.length(),
+        PlotQuantity::KineticEnergy => body.kinetic_energy,
+        PlotQuantity::PotentialEnergy => body.potential_energy,
+        PlotQuantity::Distance(other) => {
+            let other = frame.bodies.get(&other)?;
+            body.position.distance(other.position)
+        }
+    })
+}
+
+fn entity_name(names: &EntityHashMap<String>, entity: Entity) -> String {
+    names
+        .get(&entity)
+        .cloned()
+        .unwrap_or_else(|| format!("Entity #{}", entity.index()))
+}
+
+fn trace_label(trace: &PlotTrace, names: &EntityHashMap<String>) -> String {
+    let name = entity_name(names, trace.entity);
+    match trace.quantity {
+        PlotQuantity::Distance(other) => {
+            format!("{}: distance to {}", name, entity_name(names, other))
+        }
+        quantity => format!("{}: {}", name, quantity.display_name()),
+    }
+}
@@ -1,7 +1,118 @@
 use bevy_egui::egui;
 
-// [todo]
+use crate::app::SimulationControl;
 
-pub fn timeline(_ui: &mut egui::Ui) {
-    todo!();
+pub fn timeline(ui: &mut egui::Ui, control: &mut SimulationControl) {
+    let SimulationControl {
+        state,
+        recording,
+        playback,
+    } = control;
+
+    let frame_count = recording.frames().len();
+
+    ui.horizontal(|ui| {
+        ui.add_enabled_ui(frame_count > 0, |ui| {
+            let last = frame_count.saturating_sub(1);
+            let current = playback.cursor.unwrap_or(last);
+            let mut target = None;
+
+            if ui.button("⏮").on_hover_text("First frame").clicked() {
+                target = Some(0);
+            }
+            if ui.button("◀").on_hover_text("Previous frame").clicked() {
+                target = Some(current.saturating_sub(1));
+            }
+            let play_label = if playback.playing { "⏸" } else { "⏵" };
+            if ui
+                .button(play_label)
+                .on_hover_text("Play recording")
+                .clicked()
+            {
+                if playback.cursor.is_none() {
+                    target = Some(if playback.speed < 0.0 { last } else { 0 });
+                }
+                playback.playing = !playback.playing;
+            }
+            if ui.button("▶").on_hover_text("Next frame").clicked() {
+                target = Some((current + 1).min(last));
+            }
+            if ui.button("⏭").on_hover_text("Last frame").clicked() {
+                target = Some(last);
+            }
+
+            ui.separator();
+
+            let mut index = current;
+            let slider = egui::Slider::new(&mut index, 0..=last).show_value(false);
+            if ui.add(slider).changed() {
+                target = Some(index);
+                playback.playing = false;
+            }
+
+            if let Some(index) = target {
+                state.is_running = false;
+                playback.seek(recording, index);
+            }
+
+            let time = recording.frames().get(current).map_or(0.0, |f| f.time);
+            ui.label(format!(
+                "{} / {}  t = {:.3} s",
+                current + 1,
+                frame_count,
+                time
+            ));
+
+            ui.separator();
+
+            ui.label("Speed:");
+            ui.add(
+                egui::DragValue::new(&mut playback.speed)
+                    .speed(0.05)
+                    .range(-8.0..=8.0)
+                    .suffix("x"),
+            );
+        });
+
+        ui.separator();
+
+        ui.label("Branch:");
+        let active = recording.active_branch();
+        egui::ComboBox::from_id_salt("branch_selector")
+            .selected_text(&active.name)
+            .show_ui(ui, |ui| {
+                for (index, branch) in recording.branches.iter().enumerate() {
+                    let label = match branch.parent {
+                        Some((parent, time)) => format!(
+                            "{} (from {} at {:.2} s)",
+                            branch.name, recording.branches[parent].name, time
+                        ),
+                        None => branch.name.clone(),
+                    };
+                    if ui
+                        .selectable_label(index == recording.active, label)
+                        .clicked()
+                        && index != recording.active
+                    {
+                        playback.switch_to = Some(index);
+                    }
+                }
+            });
+
+        ui.separator();
+
+        ui.label("Buffer:");
+        if ui
+            .add(
+                egui::DragValue::new(&mut recording.capacity)
+                    .speed(10.0)
+                    .range(1..=100_000)
+                    .suffix(" frames"),
+            )
+            .changed()
+            && playback.cursor.is_none()
+        {
+            recording.truncate_front();
+        }
+    });
 }
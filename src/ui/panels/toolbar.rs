@@ -1,8 +1,9 @@
 use bevy_egui::egui;
 
 use crate::editor::{PlacementState, Tool};
+use crate::settings::editor_prefs::EditorPrefs;
 
-pub fn toolbar(ui: &mut egui::Ui, placement: &mut PlacementState) {
+pub fn toolbar(ui: &mut egui::Ui, placement: &mut PlacementState, editor_prefs: &mut EditorPrefs) {
     ui.horizontal(|ui| {
         ui.label("Tool");
         ui.separator();
@@ -11,5 +12,11 @@ pub fn toolbar(ui: &mut egui::Ui, placement: &mut PlacementState) {
         ui.selectable_value(&mut placement.tool, Tool::Move, "Move");
         ui.selectable_value(&mut placement.tool, Tool::Place, "Place");
         ui.selectable_value(&mut placement.tool, Tool::Delete, "Delete");
+        ui.selectable_value(&mut placement.tool, Tool::Rotate, "Rotate");
+        ui.selectable_value(&mut placement.tool, Tool::Scale, "Scale");
+        if placement.tool == Tool::Rotate {
+            ui.separator();
+            ui.checkbox(&mut editor_prefs.snap_rotation, "Snap 15°");
+        }
     });
 }
@@ -1,32 +1,41 @@
-use bevy::{camera::Viewport, prelude::*, window::PrimaryWindow};
+use bevy::{camera::Viewport, ecs::entity::EntityHashMap, prelude::*, window::PrimaryWindow};
 use bevy_egui::{
     EguiContextSettings, EguiContexts, EguiPlugin, EguiPrimaryContextPass,
     egui::{self, CentralPanel, SidePanel, TopBottomPanel},
 };
 
-use super::panels::{editor, menubar, toolbar};
-use super::resources::{GameViewTab, UiPanelVisibility, UiState};
+use super::panels::{editor, environment, menubar, plot, timeline, toolbar};
+use super::resources::{GameViewTab, PlotState, UiPanelVisibility, UiPanels, UiState};
 use crate::{
-    app::SimulationState,
+    app::{Recording, SimulationControl},
     camera::components::WorldCamera,
-    editor::{PlacementState, SelectionState, resources::PlacedEntities},
-    project::document::Document,
+    editor::{
+        BoxSelectState, DocumentHistory, EditorEntity, PickQuery, PlacedEntity, PlacementState,
+        SelectionState, SnapGuides, Tool, gizmo,
+    },
+    project::SceneEntityQuery,
     settings::editor_prefs::EditorPrefs,
-    simulation::components::{Charge, Electric, Field, Magnetic},
+    shared::WorldMousePosition,
+    simulation::{
+        Diagnostics, SimulationSettings,
+        components::{Charge, Electric, Field, Magnetic},
+    },
     ui::statusbar,
 };
-use avian2d::prelude::{LinearVelocity, RigidBody};
+use avian2d::prelude::{LinearVelocity, PhysicsDebugPlugin, RigidBody};
 
 pub struct UiPlugin;
 
 impl Plugin for UiPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EguiPlugin::default())
+            .add_plugins(PhysicsDebugPlugin::default())
+            // .insert_gizmo_config(PhysicsGizmos::all(), GizmoConfig::default())
             .insert_resource(UiState::default())
             .insert_resource(GameViewTab::default())
             .insert_resource(UiPanelVisibility::default())
+            .init_resource::<PlotState>()
             .insert_resource(EditorPrefs::default())
-            .insert_resource(Document::new())
             .add_systems(
                 EguiPrimaryContextPass,
                 (
@@ -34,7 +43,11 @@ impl Plugin for UiPlugin {
                     embed_camera_viewport,
                     draw_cursor,
                     draw_grid,
+                    draw_selection,
+                    draw_transform_gizmo,
+                    draw_snap_guides,
                     draw_field_vectors,
+                    draw_recorded_paths,
                 )
                     .chain(),
             );
@@ -44,12 +57,11 @@ impl Plugin for UiPlugin {
 fn ui_system(
     mut tab: ResMut<GameViewTab>,
     mut egui_context: EguiContexts,
-    mut state: ResMut<SimulationState>,
-    mut panel_visibility: ResMut<UiPanelVisibility>,
+    mut control: SimulationControl,
+    panels: UiPanels,
     mut placement: ResMut<PlacementState>,
     mut selection: ResMut<SelectionState>,
-    mut placed_entities: ResMut<PlacedEntities>,
-    mut document: ResMut<Document>,
+    mut session: DocumentHistory,
     entity_query: Query<
         (
             Entity,
@@ -73,24 +85,19 @@ fn ui_system(
     >,
     camera_query: Single<&mut Transform, With<WorldCamera>>,
     mut editor_prefs: ResMut<EditorPrefs>,
-    mut commands: Commands,
+    mut simulation_settings: SimulationSettings,
+    mut diagnostics: Diagnostics,
     save_entity_query: Query<
-        (
-            Entity,
-            &Transform,
-            Option<&crate::editor::EntityName>,
-            Option<&Charge>,
-            Option<&LinearVelocity>,
-            Option<&RigidBody>,
-            Option<&Magnetic>,
-            Option<&Electric>,
-            Option<&Field>,
-        ),
+        SceneEntityQuery,
         (With<crate::editor::EditorEntity>, Without<WorldCamera>),
     >,
 ) -> Result {
     tab.visible = false;
     let ctx = egui_context.ctx_mut()?;
+    let UiPanels {
+        visibility: mut panel_visibility,
+        plot: mut plot_state,
+    } = panels;
 
     TopBottomPanel::top("Menu").show(ctx, |ui| {
         menubar(
@@ -99,28 +106,46 @@ fn ui_system(
             &mut panel_visibility,
             camera_query,
             &mut editor_prefs,
-            &mut document,
+            &mut session.document,
+            &mut session.history,
             &mut selection,
             &mut placement,
-            &mut placed_entities,
+            &mut simulation_settings,
+            &control.recording,
             // entity_query,
-            save_entity_query,
-            &mut commands,
+            &save_entity_query,
         );
     });
 
     if panel_visibility.toolbar {
         TopBottomPanel::top("Tool").show(ctx, |ui| {
-            toolbar(ui, &mut placement);
+            toolbar(ui, &mut placement, &mut editor_prefs);
         });
     }
 
     if panel_visibility.statusbar {
         TopBottomPanel::bottom("Status").show(ctx, |ui| {
-            statusbar(ui, state.as_mut());
+            statusbar(ui, control.state.as_mut());
+        });
+    }
+
+    if panel_visibility.timeline {
+        TopBottomPanel::bottom("Timeline").show(ctx, |ui| {
+            timeline(ui, &mut control);
         });
     }
 
+    let entity_names: EntityHashMap<String> = entity_query
+        .iter()
+        .map(|(entity, _, name, ..)| {
+            let name = name
+                .map(|n| n.0.clone())
+                .unwrap_or_else(|| format!("Entity #{}", entity.index()));
+            (entity, name)
+        })
+        .collect();
+    let selected_entity = selection.selected_entity;
+
     if panel_visibility.editor {
         SidePanel::left("Editor")
             .default_width(250.0)
@@ -131,11 +156,39 @@ fn ui_system(
                     selection,
                     entity_query,
                     field_query,
+                    &mut simulation_settings,
+                    &diagnostics.solver,
+                    &mut plot_state,
+                    &save_entity_query,
+                    &mut session.history,
                     // commands,
                 );
             });
     }
 
+    if panel_visibility.plot {
+        SidePanel::left("Plot")
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                plot(
+                    ui,
+                    &mut plot_state,
+                    &control.recording,
+                    &control.playback,
+                    &entity_names,
+                    selected_entity,
+                );
+            });
+    }
+
+    if panel_visibility.environment {
+        SidePanel::right("Environment")
+            .default_width(220.0)
+            .show(ctx, |ui| {
+                environment(ui, &mut simulation_settings, &mut diagnostics.conservation);
+            });
+    }
+
     CentralPanel::default()
         .frame(egui::Frame::canvas(&ctx.style()).fill(egui::Color32::TRANSPARENT))
         .show(ctx, |ui| {
@@ -221,6 +274,114 @@ fn draw_grid(
     }
 }
 
+/// Outlines the selected entities, the primary one brighter, and the box of
+/// a rubber-band selection in progress.
+fn draw_selection(
+    selection: Res<SelectionState>,
+    box_select: Res<BoxSelectState>,
+    world_mouse: Res<WorldMousePosition>,
+    pick_query: Query<PickQuery, With<EditorEntity>>,
+    mut gizmos: Gizmos,
+) {
+    for item in pick_query.iter_many(selection.entities.iter().copied()) {
+        let color = if selection.selected_entity == Some(item.entity) {
+            LinearRgba::new(1.0, 0.8, 0.2, 1.0)
+        } else {
+            LinearRgba::new(1.0, 0.8, 0.2, 0.5)
+        };
+        let aabb = item.aabb();
+        let rect = Rect::from_corners(aabb.min, aabb.max).inflate(4.0);
+        gizmos.rect_2d(rect.center(), rect.size(), color);
+    }
+
+    if let (Some(start), Some(end)) = (box_select.start, world_mouse.position) {
+        let rect = Rect::from_corners(start, end);
+        gizmos.rect_2d(
+            rect.center(),
+            rect.size(),
+            LinearRgba::new(0.4, 0.7, 1.0, 0.8),
+        );
+    }
+}
+
+/// Rotate ring or resize handles of the primary selection.
+fn draw_transform_gizmo(
+    placement: Res<PlacementState>,
+    selection: Res<SelectionState>,
+    camera: Single<&Transform, With<WorldCamera>>,
+    query: Query<(&Transform, &PlacedEntity), (With<EditorEntity>, Without<WorldCamera>)>,
+    mut gizmos: Gizmos,
+) {
+    let Some(Ok((transform, placed))) = selection.selected_entity.map(|entity| query.get(entity))
+    else {
+        return;
+    };
+    let scale = camera.scale.x;
+    let color = LinearRgba::new(0.4, 0.7, 1.0, 1.0);
+    let center = transform.translation.truncate();
+
+    match placement.tool {
+        Tool::Rotate => {
+            let radius = gizmo::ring_radius(placed.size, scale);
+            gizmos.circle_2d(center, radius, color);
+            let handle = center + Vec2::from_angle(gizmo::rotation_of(transform)) * radius;
+            gizmos.line_2d(center, handle, color);
+            gizmos.circle_2d(handle, gizmo::HANDLE_RADIUS * scale, color);
+        }
+        Tool::Scale => {
+            for edge in gizmo::EDGES {
+                let handle = gizmo::edge_handle(transform, placed.size, edge);
+                gizmos.circle_2d(handle, gizmo::HANDLE_RADIUS * scale, color);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn draw_snap_guides(placement: Res<PlacementState>, guides: Res<SnapGuides>, mut gizmos: Gizmos) {
+    if !matches!(placement.tool, Tool::Place | Tool::Move) {
+        return;
+    }
+
+    for &(start, end) in &guides.lines {
+        gizmos.line_2d(start, end, LinearRgba::new(1.0, 0.3, 0.8, 0.9));
+    }
+}
+
+/// Trajectories of every recorded branch, so a resumed run can be compared
+/// against the one it was forked from.
+fn draw_recorded_paths(
+    panel_visibility: Res<UiPanelVisibility>,
+    recording: Res<Recording>,
+    mut gizmos: Gizmos,
+) {
+    if !panel_visibility.timeline {
+        return;
+    }
+
+    for (index, branch) in recording.branches.iter().enumerate() {
+        let color = if index == recording.active {
+            LinearRgba::new(1.0, 1.0, 1.0, 0.5)
+        } else {
+            LinearRgba::new(1.0, 0.6, 0.2, 0.35)
+        };
+
+        let Some(first) = branch.frames.front() else {
+            continue;
+        };
+        for entity in first.bodies.keys() {
+            gizmos.linestrip_2d(
+                branch
+                    .frames
+                    .iter()
+                    .filter_map(|frame| frame.bodies.get(entity))
+                    .map(|body| body.position),
+                color,
+            );
+        }
+    }
+}
+
 fn draw_field_vectors(
     editor_prefs: Res<EditorPrefs>,
     magnetic_query: Query<(&Field, &Transform), With<Magnetic>>,
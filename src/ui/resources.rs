@@ -1,4 +1,5 @@
-use bevy::prelude::Resource;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{Entity, ResMut, Resource};
 use bevy_egui::egui;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Resource, Default)]
@@ -8,6 +9,9 @@ pub struct ExitRequest(pub bool);
 pub struct UiPanelVisibility {
     pub toolbar: bool,
     pub editor: bool,
+    pub environment: bool,
+    pub timeline: bool,
+    pub plot: bool,
     pub statusbar: bool,
 }
 
@@ -16,11 +20,94 @@ impl Default for UiPanelVisibility {
         UiPanelVisibility {
             toolbar: true,
             editor: true,
+            environment: true,
+            timeline: true,
+            plot: false,
             statusbar: true,
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlotQuantity {
+    X,
+    Y,
+    Speed,
+    KineticEnergy,
+    PotentialEnergy,
+    /// Distance to another entity.
+    Distance(Entity),
+}
+
+impl PlotQuantity {
+    pub const SINGLE: [PlotQuantity; 5] = [
+        PlotQuantity::X,
+        PlotQuantity::Y,
+        PlotQuantity::Speed,
+        PlotQuantity::KineticEnergy,
+        PlotQuantity::PotentialEnergy,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            PlotQuantity::X => "X",
+            PlotQuantity::Y => "Y",
+            PlotQuantity::Speed => "Speed",
+            PlotQuantity::KineticEnergy => "Kinetic energy",
+            PlotQuantity::PotentialEnergy => "Potential energy",
+            PlotQuantity::Distance(_) => "Distance",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlotTrace {
+    pub entity: Entity,
+    pub quantity: PlotQuantity,
+    pub color: egui::Color32,
+}
+
+/// Quantities charted by the plot panel.
+#[derive(Resource, Default)]
+pub struct PlotState {
+    pub traces: Vec<PlotTrace>,
+}
+
+impl PlotState {
+    const COLORS: [egui::Color32; 6] = [
+        egui::Color32::from_rgb(102, 194, 255),
+        egui::Color32::from_rgb(255, 153, 51),
+        egui::Color32::from_rgb(119, 221, 119),
+        egui::Color32::from_rgb(255, 102, 128),
+        egui::Color32::from_rgb(204, 153, 255),
+        egui::Color32::from_rgb(255, 221, 85),
+    ];
+
+    pub fn add(&mut self, entity: Entity, quantity: PlotQuantity) {
+        if self
+            .traces
+            .iter()
+            .any(|trace| trace.entity == entity && trace.quantity == quantity)
+        {
+            return;
+        }
+        let color = Self::COLORS[self.traces.len() % Self::COLORS.len()];
+        self.traces.push(PlotTrace {
+            entity,
+            quantity,
+            color,
+        });
+    }
+}
+
+/// Panel layout together with the state of the panels that keep any,
+/// so `ui_system` stays within the system parameter limit.
+#[derive(SystemParam)]
+pub struct UiPanels<'w> {
+    pub visibility: ResMut<'w, UiPanelVisibility>,
+    pub plot: ResMut<'w, PlotState>,
+}
+
 #[derive(Resource)]
 pub struct UiState;
 
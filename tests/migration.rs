@@ -0,0 +1,91 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use simphy::editor::{FieldType, RigidBodyType};
+use simphy::project::{
+    ColliderData, EnvironmentData, FORMAT_VERSION, FieldData, FrictionData, ProjectError,
+    RigidBodyData, SceneData, SceneEntityData, TransformData, import_scene,
+};
+use simphy::shared::EntityShape;
+use simphy::simulation::{FieldModulation, FieldProfile};
+
+fn fixture(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "fixtures", name]
+        .iter()
+        .collect()
+}
+
+/// What `v0_scene.yaml` and `v0_scene.ron` describe, in the current format.
+fn expected_v0_scene() -> SceneData {
+    let transform = |translation| TransformData {
+        translation,
+        rotation: 0.0,
+        scale: Vec3::ONE,
+    };
+    let rigid_body = |body_type, mass| RigidBodyData {
+        body_type,
+        mass,
+        friction: FrictionData::default(),
+        initial_velocity: Vec2::ZERO,
+        angular_velocity: 0.0,
+        constant_force: Vec2::ZERO,
+    };
+
+    SceneData {
+        format_version: FORMAT_VERSION,
+        environment: EnvironmentData::default(),
+        entities: vec![
+            SceneEntityData {
+                name: "Ball".to_string(),
+                transform: transform(Vec3::new(10.0, 20.0, 0.0)),
+                color: Color::WHITE,
+                rigid_body: Some(rigid_body(RigidBodyType::Dynamic, Some(1.0))),
+                collider: Some(ColliderData {
+                    shape: EntityShape::Circle,
+                    size: Vec2::splat(20.0),
+                }),
+                charge: Some(2.0),
+                field: None,
+            },
+            SceneEntityData {
+                name: "Field".to_string(),
+                transform: transform(Vec3::ZERO),
+                color: Color::WHITE,
+                rigid_body: Some(rigid_body(RigidBodyType::Static, None)),
+                collider: Some(ColliderData {
+                    shape: EntityShape::Rectangle,
+                    size: Vec2::new(200.0, 100.0),
+                }),
+                charge: None,
+                field: Some(FieldData {
+                    field_type: FieldType::Magnetic,
+                    strength: 5.0,
+                    direction: Vec3::Z,
+                    profile: FieldProfile::default(),
+                    modulation: FieldModulation::default(),
+                }),
+            },
+        ],
+    }
+}
+
+#[test]
+fn migrates_v0_yaml() {
+    let scene = import_scene(&fixture("v0_scene.yaml")).unwrap();
+    assert_eq!(scene, expected_v0_scene());
+}
+
+#[test]
+fn migrates_v0_ron() {
+    let scene = import_scene(&fixture("v0_scene.ron")).unwrap();
+    assert_eq!(scene, expected_v0_scene());
+}
+
+#[test]
+fn rejects_unknown_v0_field_type() {
+    let result = import_scene(&fixture("v0_unknown_field.yaml"));
+    assert!(matches!(
+        result,
+        Err(ProjectError::InvalidValue { ref value, .. }) if value == "gravitational"
+    ));
+}